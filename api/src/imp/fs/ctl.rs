@@ -1,10 +1,13 @@
 use core::ffi::{c_char, c_void};
 
-use alloc::string::ToString;
+use alloc::{format, string::ToString};
 use arceos_posix_api::AT_FDCWD;
 use axerrno::{AxError, LinuxError, LinuxResult};
+use axtask::{TaskExtRef, current};
+use linux_raw_sys::general::{AT_EACCESS, AT_EMPTY_PATH, AT_SYMLINK_NOFOLLOW, R_OK, W_OK, X_OK};
 use macro_rules_attribute::apply;
 
+use super::perm::{MODE_MASK, apply_mode, fd_path, forget_mode, set_mode};
 use crate::{
     ptr::{PtrWrapper, UserConstPtr, UserPtr},
     syscall_instrument,
@@ -24,6 +27,8 @@ pub fn sys_ioctl(_fd: i32, _op: usize, _argp: UserPtr<c_void>) -> LinuxResult<is
     Ok(0)
 }
 
+/// Change the working directory of the process to `path`, which must be an
+/// existing directory (`ENOENT`/`ENOTDIR` otherwise).
 pub fn sys_chdir(path: UserConstPtr<c_char>) -> LinuxResult<isize> {
     let path = path.get_as_str()?;
     axfs::api::set_current_dir(path).map(|_| 0).map_err(|err| {
@@ -32,6 +37,172 @@ pub fn sys_chdir(path: UserConstPtr<c_char>) -> LinuxResult<isize> {
     })
 }
 
+/// Change the working directory of the process to the directory open as
+/// `fd`.
+pub fn sys_fchdir(fd: i32) -> LinuxResult<isize> {
+    arceos_posix_api::get_file_like(fd)?;
+    let dir = arceos_posix_api::Directory::from_fd(fd).map_err(|_| LinuxError::ENOTDIR)?;
+    axfs::api::set_current_dir(dir.path())
+        .map(|_| 0)
+        .map_err(|err| {
+            warn!("Failed to change directory: {err:?}");
+            err.into()
+        })
+}
+
+/// Check whether the calling process can access the file `path`, relative
+/// to `dirfd`, with the `R_OK`/`W_OK`/`X_OK` bits of `mode`, or whether it
+/// exists for `F_OK`.
+///
+/// With `AT_EMPTY_PATH` and an empty `path`, `dirfd` itself is checked. Only
+/// the owner permission bits are consulted.
+#[apply(syscall_instrument)]
+pub fn sys_faccessat(
+    dirfd: i32,
+    path: UserConstPtr<c_char>,
+    mode: u32,
+    flags: u32,
+) -> LinuxResult<isize> {
+    if mode & !(R_OK | W_OK | X_OK) != 0
+        || flags & !(AT_EACCESS | AT_SYMLINK_NOFOLLOW | AT_EMPTY_PATH) != 0
+    {
+        return Err(LinuxError::EINVAL);
+    }
+    let path = path.get_as_null_terminated()?;
+    let perm = if !path.is_empty() {
+        path_perm(dirfd, path.as_ptr())?
+    } else if flags & AT_EMPTY_PATH == 0 {
+        return Err(LinuxError::ENOENT);
+    } else if dirfd == AT_FDCWD as i32 {
+        path_perm(dirfd, c".".as_ptr())?
+    } else {
+        fd_perm(dirfd)?
+    };
+
+    if (mode & R_OK != 0 && perm & 0o400 == 0)
+        || (mode & W_OK != 0 && perm & 0o200 == 0)
+        || (mode & X_OK != 0 && perm & 0o100 == 0)
+    {
+        return Err(LinuxError::EACCES);
+    }
+    Ok(0)
+}
+
+/// The permission bits of the file `path`, relative to `dirfd`.
+fn path_perm(dirfd: i32, path: *const c_char) -> LinuxResult<u32> {
+    let path = arceos_posix_api::handle_file_path(dirfd as _, Some(path as _), false)?;
+    let perm = axfs::api::metadata(path.as_str())?.permissions().bits() as u32;
+    Ok(apply_mode(&path, perm))
+}
+
+/// The permission bits of the file open as `fd`.
+fn fd_perm(fd: i32) -> LinuxResult<u32> {
+    let mut stat = arceos_posix_api::ctypes::stat::default();
+    let res = unsafe { arceos_posix_api::sys_fstat(fd, &mut stat as *mut _) };
+    if res < 0 {
+        return Err(LinuxError::try_from(-res).unwrap());
+    }
+    if let Ok(Some(path)) = fd_path(fd) {
+        stat.st_mode = apply_mode(&path, stat.st_mode);
+    }
+    Ok(stat.st_mode)
+}
+
+pub fn sys_access(path: UserConstPtr<c_char>, mode: u32) -> LinuxResult<isize> {
+    sys_faccessat(AT_FDCWD as _, path, mode, 0)
+}
+
+/// Change the permission bits of the file open as `fd` to `mode`.
+///
+/// The mode of files other than regular files and directories, such as
+/// pipes and sockets, is not kept.
+#[apply(syscall_instrument)]
+pub fn sys_fchmod(fd: i32, mode: u32) -> LinuxResult<isize> {
+    let path = fd_path(fd)?;
+    if mode & !MODE_MASK != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    if let Some(path) = path {
+        set_mode(&path, mode);
+    }
+    Ok(0)
+}
+
+/// Change the permission bits of the file `path`, relative to `dirfd`, to
+/// `mode`. Fails with `ENOENT` if it does not exist.
+#[apply(syscall_instrument)]
+pub fn sys_fchmodat(
+    dirfd: i32,
+    path: UserConstPtr<c_char>,
+    mode: u32,
+    flags: u32,
+) -> LinuxResult<isize> {
+    if mode & !MODE_MASK != 0 || flags & !(AT_SYMLINK_NOFOLLOW | AT_EMPTY_PATH) != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let path = path.get_as_null_terminated()?;
+    if path.is_empty() {
+        if flags & AT_EMPTY_PATH == 0 {
+            return Err(LinuxError::ENOENT);
+        }
+        return sys_fchmod(dirfd, mode);
+    }
+    let path = arceos_posix_api::handle_file_path(dirfd as _, Some(path.as_ptr() as _), false)?;
+    axfs::api::metadata(path.as_str())?;
+    set_mode(&path, mode);
+    Ok(0)
+}
+
+pub fn sys_chmod(path: UserConstPtr<c_char>, mode: u32) -> LinuxResult<isize> {
+    sys_fchmodat(AT_FDCWD as _, path, mode, 0)
+}
+
+/// Write the target of the symbolic link `path`, relative to `dirfd`, into
+/// `buf` without a trailing NUL, truncated to `bufsiz` bytes, and return its
+/// length.
+///
+/// The file systems have no symbolic links, so the only link is
+/// `/proc/self/exe` (or `/proc/<pid>/exe` of the caller), which points to
+/// the executable of the process. Other existing files fail with `EINVAL`.
+#[apply(syscall_instrument)]
+pub fn sys_readlinkat(
+    dirfd: i32,
+    path: UserConstPtr<c_char>,
+    buf: UserPtr<u8>,
+    bufsiz: i32,
+) -> LinuxResult<isize> {
+    if bufsiz <= 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let path = path.get_as_null_terminated()?;
+    if path.is_empty() {
+        return Err(LinuxError::ENOENT);
+    }
+    let path = arceos_posix_api::handle_file_path(dirfd as _, Some(path.as_ptr() as _), false)?;
+
+    let curr = current();
+    let process_data = curr.task_ext().process_data();
+    let pid = curr.task_ext().thread.process().pid();
+    if path.as_str() != "/proc/self/exe" && path.as_str() != format!("/proc/{pid}/exe") {
+        axfs::api::metadata(path.as_str())?;
+        return Err(LinuxError::EINVAL);
+    }
+
+    let target = process_data.exe_path.read();
+    let len = target.len().min(bufsiz as usize);
+    let buf = buf.get_as_array(len)?;
+    unsafe { core::ptr::copy_nonoverlapping(target.as_ptr(), buf, len) };
+    Ok(len as _)
+}
+
+pub fn sys_readlink(
+    path: UserConstPtr<c_char>,
+    buf: UserPtr<u8>,
+    bufsiz: i32,
+) -> LinuxResult<isize> {
+    sys_readlinkat(AT_FDCWD as _, path, buf, bufsiz)
+}
+
 pub fn sys_mkdirat(dirfd: i32, path: UserConstPtr<c_char>, mode: u32) -> LinuxResult<isize> {
     let path = path.get_as_str()?;
 
@@ -284,8 +455,9 @@ pub fn sys_unlinkat(dir_fd: isize, path: UserConstPtr<c_char>, flags: usize) ->
                             .ok_or_else(|| {
                                 debug!("unlink file error");
                                 AxError::NotFound
-                            })
-                            .map(|_| 0)
+                            })?;
+                        forget_mode(&path);
+                        Ok(0)
                     }
                 })
             }
@@ -293,6 +465,24 @@ pub fn sys_unlinkat(dir_fd: isize, path: UserConstPtr<c_char>, flags: usize) ->
         .map_err(|err| err.into())
 }
 
+/// Write the current working directory of the process, terminated by a nul
+/// byte, into `buf` and return `buf`.
+///
+/// Fails with `ERANGE` if it does not fit into `size` bytes.
+#[apply(syscall_instrument)]
 pub fn sys_getcwd(buf: UserPtr<c_char>, size: usize) -> LinuxResult<isize> {
-    Ok(arceos_posix_api::sys_getcwd(buf.get_as_null_terminated()?.as_ptr() as _, size) as _)
+    let cwd = axfs::api::current_dir()?;
+    // The directory is kept with a trailing slash
+    let cwd = match cwd.trim_end_matches('/') {
+        "" => "/",
+        cwd => cwd,
+    };
+    if cwd.len() + 1 > size {
+        return Err(LinuxError::ERANGE);
+    }
+    let buf = buf.get_as_bytes(cwd.len() + 1)?;
+    let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, cwd.len() + 1) };
+    dst[..cwd.len()].copy_from_slice(cwd.as_bytes());
+    dst[cwd.len()] = 0;
+    Ok(buf as _)
 }
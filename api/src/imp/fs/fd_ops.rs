@@ -2,22 +2,70 @@ use core::ffi::c_int;
 use core::ffi::c_long;
 
 use arceos_posix_api as api;
-use axerrno::LinuxResult;
+use axerrno::{LinuxError, LinuxResult};
+use axtask::{TaskExtRef, current};
+use linux_raw_sys::general::{F_DUPFD_CLOEXEC, F_GETFD, F_SETFD, FD_CLOEXEC, O_CLOEXEC};
 
+use super::io::set_cloexec;
+
+/// Duplicate `old_fd` into the lowest free descriptor, which shares the open
+/// file (and so its offset) but not `FD_CLOEXEC`.
+///
+/// Fails with `EBADF` if `old_fd` is not open and with `EMFILE` if the table
+/// is full.
 pub fn sys_dup(old_fd: c_int) -> LinuxResult<isize> {
-    Ok(api::sys_dup(old_fd) as _)
+    let file = api::get_file_like(old_fd)?;
+    let new_fd = api::add_file_like(file)?;
+    set_cloexec(new_fd, false);
+    Ok(new_fd as _)
 }
 
-pub fn sys_dup3(old_fd: c_int, new_fd: c_int) -> LinuxResult<isize> {
-    Ok(api::sys_dup2(old_fd, new_fd) as _)
+pub fn sys_dup3(old_fd: c_int, new_fd: c_int, flags: c_int) -> LinuxResult<isize> {
+    let flags = flags as u32;
+    if old_fd == new_fd || flags & !O_CLOEXEC != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let fd = api::sys_dup2(old_fd, new_fd);
+    if fd >= 0 {
+        set_cloexec(fd, flags & O_CLOEXEC != 0);
+    }
+    Ok(fd as _)
 }
 
 pub fn sys_close(fd: c_int) -> LinuxResult<isize> {
-    Ok(api::sys_close(fd) as _)
+    let ret = api::sys_close(fd);
+    if ret == 0 {
+        set_cloexec(fd, false);
+    }
+    Ok(ret as _)
 }
 
 pub fn sys_fcntl(fd: c_int, cmd: c_int, arg: usize) -> LinuxResult<isize> {
-    Ok(api::sys_fcntl(fd, cmd, arg) as _)
+    match cmd as u32 {
+        F_GETFD => {
+            api::get_file_like(fd)?;
+            let cloexec = current()
+                .task_ext()
+                .process_data()
+                .cloexec_fds
+                .lock()
+                .contains(&fd);
+            Ok(if cloexec { FD_CLOEXEC as _ } else { 0 })
+        }
+        F_SETFD => {
+            api::get_file_like(fd)?;
+            set_cloexec(fd, arg as u32 & FD_CLOEXEC != 0);
+            Ok(0)
+        }
+        F_DUPFD_CLOEXEC => {
+            let new_fd = api::sys_fcntl(fd, cmd, arg);
+            if new_fd >= 0 {
+                set_cloexec(new_fd, true);
+            }
+            Ok(new_fd as _)
+        }
+        _ => Ok(api::sys_fcntl(fd, cmd, arg) as _),
+    }
 }
 
 pub fn sys_lseek(fd: c_int, offset: c_long, whence: i32) -> LinuxResult<isize> {
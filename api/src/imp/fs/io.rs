@@ -1,7 +1,10 @@
 use core::ffi::{CStr, c_char, c_void};
 
 use arceos_posix_api::{self as api, ctypes::mode_t};
-use axerrno::LinuxResult;
+use axerrno::{LinuxError, LinuxResult};
+use axsync::Mutex;
+use axtask::{TaskExtRef, current};
+use linux_raw_sys::general::{O_CLOEXEC, O_CREAT, O_EXCL, UIO_MAXIOV};
 
 use crate::ptr::{PtrWrapper, UserConstPtr, UserPtr};
 
@@ -15,15 +18,72 @@ pub fn sys_write(fd: i32, buf: UserConstPtr<c_void>, count: usize) -> LinuxResul
     Ok(api::sys_write(fd, buf, count))
 }
 
+/// Check the `iovec` array of `readv`/`writev`: `iocnt` must be in
+/// `0..=UIO_MAXIOV` and the segment lengths must add up to at most
+/// `isize::MAX`.
+fn user_iovecs<'a>(
+    iov: UserConstPtr<api::ctypes::iovec>,
+    iocnt: i32,
+) -> LinuxResult<&'a [api::ctypes::iovec]> {
+    if !(0..=UIO_MAXIOV as i32).contains(&iocnt) {
+        return Err(LinuxError::EINVAL);
+    }
+    if iocnt == 0 {
+        return Ok(&[]);
+    }
+    let iovs = unsafe { core::slice::from_raw_parts(iov.get_as_array(iocnt as _)?, iocnt as _) };
+    iovs.iter()
+        .try_fold(0isize, |total, iov| total.checked_add_unsigned(iov.iov_len))
+        .ok_or(LinuxError::EINVAL)?;
+    Ok(iovs)
+}
+
+/// Transfer the segments in turn with `f`, stopping at the first short
+/// transfer. An error is only returned if nothing was transferred.
+fn transfer_iovecs(
+    iovs: &[api::ctypes::iovec],
+    mut f: impl FnMut(usize, usize) -> LinuxResult<isize>,
+) -> LinuxResult<isize> {
+    let mut total = 0;
+    for iov in iovs.iter().filter(|iov| iov.iov_len > 0) {
+        let n = match f(iov.iov_base as usize, iov.iov_len) {
+            Ok(n) if n >= 0 => n,
+            result if total == 0 => return result,
+            _ => break,
+        };
+        total += n;
+        if (n as usize) < iov.iov_len {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+pub fn sys_readv(fd: i32, iov: UserConstPtr<api::ctypes::iovec>, iocnt: i32) -> LinuxResult<isize> {
+    transfer_iovecs(user_iovecs(iov, iocnt)?, |base, len| {
+        sys_read(fd, base.into(), len)
+    })
+}
+
 pub fn sys_writev(
     fd: i32,
     iov: UserConstPtr<api::ctypes::iovec>,
     iocnt: i32,
 ) -> LinuxResult<isize> {
-    let iov = iov.get_as_bytes(iocnt as _)?;
-    unsafe { Ok(api::sys_writev(fd, iov, iocnt)) }
+    transfer_iovecs(user_iovecs(iov, iocnt)?, |base, len| {
+        sys_write(fd, base.into(), len)
+    })
 }
 
+/// Held while a file may be created, so that checking for an existing file
+/// and creating it can not interleave with another creation.
+static CREATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Open the file `path` relative to `dirfd`.
+///
+/// `O_DIRECTORY` opens directories only, failing with `ENOTDIR` otherwise.
+/// With `O_CREAT | O_EXCL`, exactly one of several concurrent opens of the
+/// same missing file succeeds, the others fail with `EEXIST`.
 pub fn sys_openat(
     dirfd: i32,
     path: UserConstPtr<c_char>,
@@ -31,7 +91,31 @@ pub fn sys_openat(
     modes: mode_t,
 ) -> LinuxResult<isize> {
     let path = path.get_as_null_terminated()?;
-    Ok(api::sys_openat(dirfd, path.as_ptr(), flags, modes) as _)
+    let flags = flags as u32;
+
+    let _create = (flags & O_CREAT != 0).then(|| CREATE_LOCK.lock());
+    if flags & (O_CREAT | O_EXCL) == O_CREAT | O_EXCL {
+        let abs_path = api::handle_file_path(dirfd as _, Some(path.as_ptr() as _), false)?;
+        if abs_path.exists() {
+            return Err(LinuxError::EEXIST);
+        }
+    }
+    let fd = api::sys_openat(dirfd, path.as_ptr(), flags as _, modes);
+    if fd >= 0 {
+        set_cloexec(fd, flags & O_CLOEXEC != 0);
+    }
+    Ok(fd as _)
+}
+
+/// Set or clear the close-on-exec flag of `fd` in the current process.
+pub(crate) fn set_cloexec(fd: i32, cloexec: bool) {
+    let curr = current();
+    let mut cloexec_fds = curr.task_ext().process_data().cloexec_fds.lock();
+    if cloexec {
+        cloexec_fds.insert(fd);
+    } else {
+        cloexec_fds.remove(&fd);
+    }
 }
 
 pub fn sys_open(path: UserConstPtr<c_char>, flags: i32, modes: mode_t) -> LinuxResult<isize> {
@@ -42,6 +126,7 @@ pub fn sys_open(path: UserConstPtr<c_char>, flags: i32, modes: mode_t) -> LinuxR
 pub fn sys_unlink(pathname: UserConstPtr<c_char>) -> LinuxResult<isize> {
     let path_name = pathname.get_as_str()?;
     //ax_println!("{}", path_name);
+    let path = api::FilePath::new(path_name)?;
     let (dir_prefix, file_name) = path_name.rsplit_once('/').unwrap();
 
     //ax_println!("axfs::fops::Directory::open({})", dir_prefix);
@@ -49,6 +134,7 @@ pub fn sys_unlink(pathname: UserConstPtr<c_char>) -> LinuxResult<isize> {
         axfs::fops::Directory::open_dir(dir_prefix, &axfs::fops::OpenOptions::new().set_read(true))
             .unwrap();
     dir.remove_file(file_name);
+    super::perm::forget_mode(&path);
     // ax_println!("Please don't go💔");
     Ok(0)
 }
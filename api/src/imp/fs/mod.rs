@@ -2,7 +2,9 @@ mod ctl;
 mod fd_ops;
 mod io;
 mod mount;
+mod perm;
 mod pipe;
+mod poll;
 mod stat;
 
 pub use self::ctl::*;
@@ -10,4 +12,5 @@ pub use self::fd_ops::*;
 pub use self::io::*;
 pub use self::mount::*;
 pub use self::pipe::*;
+pub use self::poll::*;
 pub use self::stat::*;
@@ -0,0 +1,52 @@
+//! Permission bits set by `chmod`.
+//!
+//! The file systems behind axfs can not store permissions, so the kernel
+//! keeps the bits given to `chmod` itself, keyed by the real path of the file,
+//! and reports them in place of the file system's.
+
+use alloc::{collections::BTreeMap, string::String};
+use arceos_posix_api::{self as api, FilePath};
+use axerrno::LinuxResult;
+use axsync::Mutex;
+
+/// Permission bits `chmod` can set, including set-user-ID, set-group-ID and
+/// sticky.
+pub(crate) const MODE_MASK: u32 = 0o7777;
+
+static MODES: Mutex<BTreeMap<String, u32>> = Mutex::new(BTreeMap::new());
+
+/// The path of the file or directory open as `fd`, `None` for other kinds
+/// of files such as pipes and sockets, which have no path to keep
+/// permissions for.
+pub(crate) fn fd_path(fd: i32) -> LinuxResult<Option<FilePath>> {
+    let file = api::get_file_like(fd)?.into_any();
+    let path = match file.downcast::<api::File>() {
+        Ok(file) => FilePath::new(file.path())?,
+        Err(file) => match file.downcast::<api::Directory>() {
+            Ok(dir) => FilePath::new(dir.path())?,
+            Err(_) => return Ok(None),
+        },
+    };
+    Ok(Some(path))
+}
+
+/// Set the permission bits of `path` to `mode`.
+pub(crate) fn set_mode(path: &FilePath, mode: u32) {
+    MODES.lock().insert(path.as_str().into(), mode & MODE_MASK);
+}
+
+/// Replace the permission bits of the file mode `st_mode` of `path` with the
+/// ones set by `chmod`, if any.
+pub(crate) fn apply_mode(path: &FilePath, st_mode: u32) -> u32 {
+    match MODES.lock().get(path.as_str()) {
+        Some(mode) => (st_mode & !MODE_MASK) | mode,
+        None => st_mode,
+    }
+}
+
+/// Forget the permission bits of `path` once it has been removed.
+pub(crate) fn forget_mode(path: &FilePath) {
+    if !path.exists() {
+        MODES.lock().remove(path.as_str());
+    }
+}
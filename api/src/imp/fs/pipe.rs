@@ -1,16 +1,34 @@
 use core::ffi::c_int;
 
 use arceos_posix_api as api;
-use axerrno::LinuxResult;
+use axerrno::{LinuxError, LinuxResult};
+use linux_raw_sys::general::{F_SETFL, O_CLOEXEC, O_NONBLOCK};
 
+use super::io::set_cloexec;
 use crate::ptr::{PtrWrapper, UserPtr};
 
 pub fn sys_pipe(fds: UserPtr<i32>) -> LinuxResult<isize> {
-    unimplemented!("? sys_pipe2");
+    sys_pipe2(fds, 0)
 }
 
-pub fn sys_pipe2(fds: UserPtr<i32>) -> LinuxResult<isize> {
+pub fn sys_pipe2(fds: UserPtr<i32>, flags: c_int) -> LinuxResult<isize> {
+    let flags = flags as u32;
+    if flags & !(O_CLOEXEC | O_NONBLOCK) != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+
     let fds = fds.get_as_array(2)?;
     let fds_slice: &mut [c_int] = unsafe { core::slice::from_raw_parts_mut(fds, 2) };
-    Ok(api::sys_pipe(fds_slice) as _)
+    let ret = api::sys_pipe(fds_slice);
+    if ret < 0 {
+        return Ok(ret as _);
+    }
+
+    for &fd in fds_slice.iter() {
+        set_cloexec(fd, flags & O_CLOEXEC != 0);
+        if flags & O_NONBLOCK != 0 {
+            api::sys_fcntl(fd, F_SETFL as _, O_NONBLOCK as _);
+        }
+    }
+    Ok(0)
 }
@@ -0,0 +1,114 @@
+use core::time::Duration;
+
+use arceos_posix_api::{self as api, ctypes::timespec};
+use axerrno::{LinuxError, LinuxResult};
+use axtask::{TaskExtRef, current};
+use linux_raw_sys::general::{POLLERR, POLLIN, POLLNVAL, POLLOUT};
+use starry_core::signal::{self, SigMask, UNBLOCKABLE};
+
+use crate::imp::task::timespec_to_duration;
+use crate::ptr::{PtrWrapper, UserConstPtr, UserPtr};
+
+/// `struct pollfd` as used by `ppoll`.
+#[repr(C)]
+pub struct PollFd {
+    fd: i32,
+    events: i16,
+    revents: i16,
+}
+
+/// Size of the fd table (`AX_FILE_LIMIT`), more entries would be duplicates.
+const MAX_FDS: usize = 1024;
+
+/// How long to sleep between polls, as files can not wake up waiters.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Fill in `revents` of every entry and return how many have any set.
+///
+/// Entries with a negative `fd` are skipped, unknown fds report `POLLNVAL`.
+fn poll_fds(fds: &mut [PollFd]) -> usize {
+    let mut ready = 0;
+    for pfd in fds.iter_mut() {
+        pfd.revents = 0;
+        if pfd.fd < 0 {
+            continue;
+        }
+        let revents = match api::get_file_like(pfd.fd).and_then(|file| file.poll()) {
+            Ok(state) => {
+                let mut revents = 0;
+                if state.readable {
+                    revents |= POLLIN;
+                }
+                if state.writable {
+                    revents |= POLLOUT;
+                }
+                revents & pfd.events as u32
+            }
+            Err(LinuxError::EBADF) => POLLNVAL,
+            Err(_) => POLLERR,
+        };
+        pfd.revents = revents as i16;
+        ready += (revents != 0) as usize;
+    }
+    ready
+}
+
+/// Wait until one of `fds` is ready for reading or writing, or `timeout`
+/// (if given) has elapsed, with the blocked signals replaced by `sigmask`
+/// (if given) meanwhile.
+///
+/// Returns the number of ready entries, 0 on timeout, and `EINTR` if a
+/// signal arrives first.
+pub fn sys_ppoll(
+    fds: UserPtr<PollFd>,
+    nfds: usize,
+    timeout: UserConstPtr<timespec>,
+    sigmask: UserConstPtr<u64>,
+    sigsetsize: usize,
+) -> LinuxResult<isize> {
+    if nfds > MAX_FDS {
+        return Err(LinuxError::EINVAL);
+    }
+    let fds = if nfds == 0 {
+        &mut []
+    } else {
+        unsafe { core::slice::from_raw_parts_mut(fds.get_as_array(nfds)?, nfds) }
+    };
+    let timeout = timeout
+        .nullable(UserConstPtr::get)?
+        .map(|timeout| timespec_to_duration(unsafe { timeout.read() }))
+        .transpose()?;
+    let sigmask = sigmask.nullable(UserConstPtr::get)?;
+    debug!("sys_ppoll <= nfds: {}, timeout: {:?}", nfds, timeout);
+
+    if let Some(sigmask) = sigmask {
+        if sigsetsize != size_of::<u64>() {
+            return Err(LinuxError::EINVAL);
+        }
+        // Like `rt_sigsuspend`, the old mask is restored once a signal has
+        // been delivered or the syscall returns.
+        let mask = SigMask::from_sigset(unsafe { sigmask.read() });
+        let curr = current();
+        let thread_data = curr.task_ext().thread_data();
+        let old = core::mem::replace(&mut *thread_data.blocked.lock(), mask - UNBLOCKABLE);
+        *thread_data.saved.lock() = Some(old);
+    }
+
+    let deadline = timeout.map(|timeout| axhal::time::wall_time() + timeout);
+    loop {
+        let ready = poll_fds(fds);
+        if ready > 0 {
+            return Ok(ready as _);
+        }
+        let left = match deadline {
+            Some(deadline) => deadline.saturating_sub(axhal::time::wall_time()),
+            None => POLL_INTERVAL,
+        };
+        if left.is_zero() {
+            return Ok(0);
+        }
+        if signal::sleep_interruptible(left.min(POLL_INTERVAL)).is_some() {
+            return Err(LinuxError::EINTR);
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use core::ffi::c_char;
 
 use axerrno::{LinuxError, LinuxResult};
+use linux_raw_sys::general::{AT_EMPTY_PATH, AT_SYMLINK_NOFOLLOW};
 use macro_rules_attribute::apply;
 
+use super::perm::{apply_mode, fd_path};
 use crate::{
     ptr::{PtrWrapper, UserConstPtr, UserPtr},
     syscall_instrument,
@@ -157,6 +159,9 @@ pub fn sys_fstat(fd: i32, kstatbuf: UserPtr<Kstat>) -> LinuxResult<isize> {
     if result < 0 {
         return Ok(result as _);
     }
+    if let Ok(Some(path)) = fd_path(fd) {
+        statbuf.st_mode = apply_mode(&path, statbuf.st_mode);
+    }
 
     unsafe {
         let kstat = Kstat::from(statbuf);
@@ -165,28 +170,49 @@ pub fn sys_fstat(fd: i32, kstatbuf: UserPtr<Kstat>) -> LinuxResult<isize> {
     Ok(0)
 }
 
+/// Get the status of `path` relative to `dir_fd`.
+///
+/// With `AT_EMPTY_PATH` and an empty `path`, `dir_fd` itself is queried. With
+/// `AT_SYMLINK_NOFOLLOW`, a symbolic link is not dereferenced.
 #[apply(syscall_instrument)]
 pub fn sys_fstatat(
     dir_fd: isize,
     path: UserConstPtr<c_char>,
     kstatbuf: UserPtr<Kstat>,
-    _flags: i32,
+    flags: u32,
 ) -> LinuxResult<isize> {
+    if flags & !(AT_EMPTY_PATH | AT_SYMLINK_NOFOLLOW) != 0 {
+        return Err(LinuxError::EINVAL);
+    }
     let path = path.get_as_null_terminated()?;
+    if path.is_empty() {
+        if flags & AT_EMPTY_PATH == 0 {
+            return Err(LinuxError::ENOENT);
+        }
+        return sys_fstat(dir_fd as _, kstatbuf);
+    }
     let path = arceos_posix_api::handle_file_path(dir_fd, Some(path.as_ptr() as _), false)?;
 
     let kstatbuf = kstatbuf.get()?;
 
     let mut statbuf = arceos_posix_api::ctypes::stat::default();
     let result = unsafe {
-        arceos_posix_api::sys_stat(
-            path.as_ptr() as _,
-            &mut statbuf as *mut arceos_posix_api::ctypes::stat,
-        )
+        if flags & AT_SYMLINK_NOFOLLOW != 0 {
+            arceos_posix_api::sys_lstat(
+                path.as_ptr() as _,
+                &mut statbuf as *mut arceos_posix_api::ctypes::stat,
+            ) as _
+        } else {
+            arceos_posix_api::sys_stat(
+                path.as_ptr() as _,
+                &mut statbuf as *mut arceos_posix_api::ctypes::stat,
+            )
+        }
     };
     if result < 0 {
         return Ok(result as _);
     }
+    statbuf.st_mode = apply_mode(&path, statbuf.st_mode);
 
     unsafe {
         let kstat = Kstat::from(statbuf);
@@ -303,6 +329,9 @@ pub fn sys_statx(
         if res < 0 {
             return Err(LinuxError::try_from(-res).unwrap());
         }
+        if let Ok(Some(path)) = fd_path(dirfd) {
+            status.st_mode = apply_mode(&path, status.st_mode);
+        }
         let statx = unsafe { &mut *statxbuf.get()? };
         statx.stx_blksize = status.st_blksize as u32;
         statx.stx_attributes = status.st_mode as u64;
@@ -2,6 +2,7 @@ use alloc::vec;
 use axerrno::{LinuxError, LinuxResult};
 use axhal::paging::MappingFlags;
 use axtask::{TaskExtRef, current};
+use linux_raw_sys::general::*;
 use macro_rules_attribute::apply;
 use memory_addr::{VirtAddr, VirtAddrRange};
 
@@ -66,6 +67,13 @@ bitflags::bitflags! {
     }
 }
 
+/// Maps `length` bytes at `addr` (only a hint unless `MAP_FIXED` is given),
+/// either zero-filled with `MAP_ANONYMOUS` or with the contents of `fd` from
+/// `offset` on.
+///
+/// Returns `EINVAL` if `length` is 0, `offset` is not page aligned, `addr` is
+/// not page aligned with `MAP_FIXED`, or not exactly one of `MAP_SHARED` and
+/// `MAP_PRIVATE` is given.
 #[apply(syscall_instrument)]
 pub fn sys_mmap(
     addr: UserPtr<usize>,
@@ -76,43 +84,39 @@ pub fn sys_mmap(
     offset: isize,
 ) -> LinuxResult<isize> {
     // Safety: addr is used for mapping, and we won't directly access it.
-    let mut addr = unsafe { addr.into_inner() };
+    let addr = unsafe { addr.into_inner() } as usize;
 
-    let curr = current();
-    let process_data = curr.task_ext().process_data();
-    let mut aspace = process_data.aspace.lock();
     let permission_flags = MmapProt::from_bits_truncate(prot);
-    // TODO: check illegal flags for mmap
-    // An example is the flags contained none of MAP_PRIVATE, MAP_SHARED, or MAP_SHARED_VALIDATE.
     let map_flags = MmapFlags::from_bits_truncate(flags);
-    let mut aligned_length = length;
-
-    if addr.is_null() {
-        aligned_length = memory_addr::align_up_4k(aligned_length);
-    } else {
-        let start = addr as usize;
-        let mut end = start + aligned_length;
-        addr = memory_addr::align_down_4k(start) as *mut usize;
-        end = memory_addr::align_up_4k(end);
-        aligned_length = end - start;
-    }
-
     info!(
-        "mmap: addr: {:?}, length: {:x?}, prot: {:?}, flags: {:?}, fd: {:?}, offset: {:?}",
+        "mmap: addr: {:#x}, length: {:x?}, prot: {:?}, flags: {:?}, fd: {:?}, offset: {:?}",
         addr, length, permission_flags, map_flags, fd, offset
     );
 
+    let shared = map_flags.contains(MmapFlags::MAP_SHARED);
+    let private = map_flags.contains(MmapFlags::MAP_PRIVATE);
+    if length == 0 || shared == private || !memory_addr::is_aligned_4k(offset as usize) {
+        return Err(LinuxError::EINVAL);
+    }
+    let aligned_length = length
+        .checked_next_multiple_of(memory_addr::PAGE_SIZE_4K)
+        .ok_or(LinuxError::ENOMEM)?;
+
+    let curr = current();
+    let process_data = curr.task_ext().process_data();
+    let mut aspace = process_data.aspace.lock();
+
     let start_addr = if map_flags.contains(MmapFlags::MAP_FIXED) {
-        if addr.is_null() {
+        if addr == 0 || !memory_addr::is_aligned_4k(addr) {
             return Err(LinuxError::EINVAL);
         }
-        let dst_addr = VirtAddr::from(addr as usize);
+        let dst_addr = VirtAddr::from(addr);
         aspace.unmap(dst_addr, aligned_length)?;
         dst_addr
     } else {
         aspace
             .find_free_area(
-                VirtAddr::from(addr as usize),
+                VirtAddr::from(memory_addr::align_down_4k(addr)),
                 aligned_length,
                 VirtAddrRange::new(aspace.base(), aspace.end()),
             )
@@ -157,21 +161,35 @@ pub fn sys_mmap(
     Ok(start_addr.as_usize() as _)
 }
 
+/// Unmaps the pages in `[addr, addr + length)`. Pages of the range which
+/// are not mapped are skipped.
+///
+/// Returns `EINVAL` if `addr` is not page aligned or `length` is 0.
 #[apply(syscall_instrument)]
 pub fn sys_munmap(addr: UserPtr<usize>, length: usize) -> LinuxResult<isize> {
     // Safety: addr is used for mapping, and we won't directly access it.
-    let addr = unsafe { addr.into_inner() };
+    let addr = unsafe { addr.into_inner() } as usize;
+
+    if length == 0 || !memory_addr::is_aligned_4k(addr) {
+        return Err(LinuxError::EINVAL);
+    }
+    let length = length
+        .checked_next_multiple_of(memory_addr::PAGE_SIZE_4K)
+        .ok_or(LinuxError::EINVAL)?;
 
     let curr = current();
     let process_data = curr.task_ext().process_data();
     let mut aspace = process_data.aspace.lock();
-    let length = memory_addr::align_up_4k(length);
-    let start_addr = VirtAddr::from(addr as usize);
+    let start_addr = VirtAddr::from(addr);
     aspace.unmap(start_addr, length)?;
     axhal::arch::flush_tlb(None);
     Ok(0)
 }
 
+/// Changes the access protections of the pages in `[addr, addr + length)`.
+///
+/// Returns `EINVAL` if `addr` is not page aligned or `prot` is invalid, and
+/// `ENOMEM` if any page of the range is not mapped.
 #[apply(syscall_instrument)]
 pub fn sys_mprotect(addr: UserPtr<usize>, length: usize, prot: i32) -> LinuxResult<isize> {
     // Safety: addr is used for mapping, and we won't directly access it.
@@ -184,13 +202,51 @@ pub fn sys_mprotect(addr: UserPtr<usize>, length: usize, prot: i32) -> LinuxResu
     if permission_flags.contains(MmapProt::PROT_GROWDOWN | MmapProt::PROT_GROWSUP) {
         return Err(LinuxError::EINVAL);
     }
+    if !memory_addr::is_aligned_4k(addr as usize) {
+        return Err(LinuxError::EINVAL);
+    }
+    if length == 0 {
+        return Ok(0);
+    }
 
     let curr = current();
     let process_data = curr.task_ext().process_data();
     let mut aspace = process_data.aspace.lock();
     let length = memory_addr::align_up_4k(length);
     let start_addr = VirtAddr::from(addr as usize);
+    let range = VirtAddrRange::try_from_start_size(start_addr, length).ok_or(LinuxError::ENOMEM)?;
+    if !aspace.check_region_access(range, MappingFlags::empty()) {
+        return Err(LinuxError::ENOMEM);
+    }
     aspace.protect(start_addr, length, permission_flags.into())?;
+    axhal::arch::flush_tlb(None);
 
     Ok(0)
 }
+
+/// Gives advice about the use of memory in `[addr, addr + length)`.
+///
+/// The advice is only validated and otherwise ignored. `addr` must be page
+/// aligned and `advice` one of the `MADV_*` values Linux knows, otherwise
+/// `EINVAL` is returned.
+#[apply(syscall_instrument)]
+pub fn sys_madvise(addr: UserPtr<usize>, length: usize, advice: i32) -> LinuxResult<isize> {
+    // Safety: addr is only validated, and we won't directly access it.
+    let addr = unsafe { addr.into_inner() } as usize;
+
+    info!(
+        "madvise: addr: {:#x}, length: {:#x}, advice: {}",
+        addr, length, advice
+    );
+    if !memory_addr::is_aligned_4k(addr) || addr.checked_add(length).is_none() {
+        return Err(LinuxError::EINVAL);
+    }
+    match advice as u32 {
+        MADV_NORMAL | MADV_RANDOM | MADV_SEQUENTIAL | MADV_WILLNEED | MADV_DONTNEED | MADV_FREE
+        | MADV_REMOVE | MADV_DONTFORK | MADV_DOFORK | MADV_HWPOISON | MADV_SOFT_OFFLINE
+        | MADV_MERGEABLE | MADV_UNMERGEABLE | MADV_HUGEPAGE | MADV_NOHUGEPAGE | MADV_DONTDUMP
+        | MADV_DODUMP | MADV_WIPEONFORK | MADV_KEEPONFORK | MADV_COLD | MADV_PAGEOUT
+        | MADV_POPULATE_READ | MADV_POPULATE_WRITE | MADV_DONTNEED_LOCKED | MADV_COLLAPSE => Ok(0),
+        _ => Err(LinuxError::EINVAL),
+    }
+}
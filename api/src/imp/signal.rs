@@ -1,66 +1,295 @@
-use core::ffi::{c_int, c_void};
+use core::ffi::c_int;
 
-use axerrno::LinuxResult;
+use axerrno::{LinuxError, LinuxResult};
+use axhal::arch::TrapFrame;
 use axtask::{TaskExtRef, current};
+use linux_raw_sys::general::{
+    FUTEX_CLOCK_REALTIME, FUTEX_PRIVATE_FLAG, FUTEX_WAIT, FUTEX_WAKE, SIG_BLOCK, SIG_SETMASK,
+    SIG_UNBLOCK,
+};
 
+use super::task::timespec_to_duration;
 use crate::ptr::{PtrWrapper, UserConstPtr, UserPtr};
 
 use arceos_posix_api as api;
 use arceos_posix_api::ctypes::rlimit;
 use arceos_posix_api::ctypes::timespec;
 
+use starry_core::futex;
 use starry_core::mm::AddrSpace;
-use starry_core::signal::{self, SigMask, Signal};
+use starry_core::signal::{
+    self, QueuedSigInfo, SI_TKILL, SI_USER, SigActionFlags, SigDisposition, SigInfo, SigMask,
+    SignalAction, UNBLOCKABLE,
+};
 
 pub fn sys_rt_sigprocmask(
-    _how: i32,
-    _set: UserConstPtr<c_void>,
-    _oldset: UserPtr<c_void>,
-    _sigsetsize: usize,
+    how: i32,
+    set: UserConstPtr<u64>,
+    oldset: UserPtr<u64>,
+    sigsetsize: usize,
 ) -> LinuxResult<isize> {
-    warn!("sys_rt_sigprocmask: not implemented");
+    if sigsetsize != size_of::<u64>() {
+        return Err(LinuxError::EINVAL);
+    }
+    let set = set
+        .nullable(UserConstPtr::get)?
+        .map(|set| SigMask::from_sigset(unsafe { set.read() }));
+    let oldset = oldset.nullable(UserPtr::get)?;
+
+    let curr = current();
+    let mut blocked = curr.task_ext().thread_data().blocked.lock();
+    let old = *blocked;
+    if let Some(set) = set {
+        let mask = match how as u32 {
+            SIG_BLOCK => old | set,
+            SIG_UNBLOCK => old - set,
+            SIG_SETMASK => set,
+            _ => return Err(LinuxError::EINVAL),
+        };
+        *blocked = mask - UNBLOCKABLE;
+    }
+    drop(blocked);
+
+    if let Some(oldset) = oldset {
+        unsafe { oldset.write(old.to_sigset()) };
+    }
+    Ok(0)
+}
+
+/// Store the signals which are pending while blocked in `set`.
+pub fn sys_rt_sigpending(set: UserPtr<u64>, sigsetsize: usize) -> LinuxResult<isize> {
+    if sigsetsize != size_of::<u64>() {
+        return Err(LinuxError::EINVAL);
+    }
+    let set = set.get()?;
+    let blocked = *current().task_ext().thread_data().blocked.lock();
+    let pending = signal::pending_signals() & blocked;
+    unsafe { set.write(pending.to_sigset()) };
     Ok(0)
 }
 
-// TODO
+/// Temporarily replace the blocked signals with `mask` and wait until a
+/// signal which is not ignored arrives. The previous mask is restored once
+/// the signal has been delivered.
+pub fn sys_rt_sigsuspend(mask: UserConstPtr<u64>, sigsetsize: usize) -> LinuxResult<isize> {
+    if sigsetsize != size_of::<u64>() {
+        return Err(LinuxError::EINVAL);
+    }
+    let mask = SigMask::from_sigset(unsafe { mask.get()?.read() });
+
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    let old = core::mem::replace(&mut *thread_data.blocked.lock(), mask - UNBLOCKABLE);
+    *thread_data.saved.lock() = Some(old);
+
+    signal::wait_for_signal();
+    Err(LinuxError::EINTR)
+}
+
+const SIG_DFL: usize = 0;
+const SIG_IGN: usize = 1;
+
+/// `struct sigaction` as passed to `rt_sigaction`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct KernelSigaction {
+    handler: usize,
+    flags: usize,
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    restorer: usize,
+    mask: u64,
+}
+
+impl From<KernelSigaction> for SignalAction {
+    fn from(act: KernelSigaction) -> Self {
+        let disposition = match act.handler {
+            SIG_DFL => SigDisposition::Default,
+            SIG_IGN => SigDisposition::Ignore,
+            handler => SigDisposition::Handler(handler),
+        };
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        let sa_restorer = act.restorer;
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        let sa_restorer = 0;
+        Self {
+            disposition,
+            sa_mask: SigMask::from_sigset(act.mask),
+            sa_flags: SigActionFlags::from_bits_truncate(act.flags as u32),
+            sa_restorer,
+        }
+    }
+}
+
+impl From<&SignalAction> for KernelSigaction {
+    fn from(action: &SignalAction) -> Self {
+        let handler = match action.disposition {
+            SigDisposition::Ignore => SIG_IGN,
+            SigDisposition::Handler(handler) => handler,
+            _ => SIG_DFL,
+        };
+        Self {
+            handler,
+            flags: action.sa_flags.bits() as usize,
+            #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+            restorer: action.sa_restorer,
+            mask: action.sa_mask.to_sigset(),
+        }
+    }
+}
+
 pub fn sys_rt_sigaction(
-    _signum: i32,
-    _act: UserConstPtr<c_void>,
-    _oldact: UserPtr<c_void>,
-    _sigsetsize: usize,
+    signum: i32,
+    act: UserConstPtr<KernelSigaction>,
+    oldact: UserPtr<KernelSigaction>,
+    sigsetsize: usize,
 ) -> LinuxResult<isize> {
-    warn!("sys_rt_sigaction: not implemented");
+    if sigsetsize != size_of::<u64>() {
+        return Err(LinuxError::EINVAL);
+    }
+    let mask = SigMask::from_signo(signum as u32).ok_or(LinuxError::EINVAL)?;
+    let act = act
+        .nullable(UserConstPtr::get)?
+        .map(|act| unsafe { act.read() });
+    let oldact = oldact.nullable(UserPtr::get)?;
+
+    if act.is_some() && UNBLOCKABLE.intersects(mask) {
+        return Err(LinuxError::EINVAL);
+    }
+
+    let curr = current();
+    let mut actions = curr.task_ext().process_data().actions.lock();
+    let action = &mut actions[signum as usize];
+    let old = KernelSigaction::from(&*action);
+    if let Some(act) = act {
+        *action = act.into();
+    }
+    drop(actions);
+
+    if let Some(oldact) = oldact {
+        unsafe { oldact.write(old) };
+    }
     Ok(0)
 }
 
-// TODO
+/// Return from a signal handler to the context saved when it was invoked.
+pub fn sys_rt_sigreturn(tf: &mut TrapFrame) -> LinuxResult<isize> {
+    signal::restore_signal_frame(tf);
+    // The syscall return value is written back into `tf`, so hand back the
+    // restored one.
+    Ok(tf.retval() as isize)
+}
+
+/// Wait on or wake the futex at `uaddr`.
+///
+/// Only `FUTEX_WAIT` and `FUTEX_WAKE` are supported. Futexes are always
+/// private to the address space, with or without `FUTEX_PRIVATE_FLAG`.
 pub fn sys_futex(
     uaddr: UserPtr<i32>,
     futex_op: c_int,
     val: c_int,
     timeout: UserPtr<timespec>,
-    uaddr2: UserPtr<i32>,
-    val3: c_int,
+    _uaddr2: UserPtr<i32>,
+    _val3: c_int,
 ) -> LinuxResult<isize> {
-    //unimplemented!("😅: sys_futex");
-    Ok(0)
+    let op = futex_op as u32 & !(FUTEX_PRIVATE_FLAG | FUTEX_CLOCK_REALTIME);
+    match op {
+        FUTEX_WAIT => {
+            let timeout = timeout
+                .nullable(UserPtr::get)?
+                .map(|timeout| timespec_to_duration(unsafe { timeout.read() }))
+                .transpose()?;
+            futex::futex_wait(uaddr.get()?, val, timeout)
+        }
+        FUTEX_WAKE => Ok(futex::futex_wake(uaddr.address().as_usize(), val.max(0) as usize) as _),
+        _ => {
+            warn!("sys_futex: unsupported op {:#x}", futex_op);
+            Err(LinuxError::ENOSYS)
+        }
+    }
 }
 
 pub fn sys_rt_kill(pid: c_int, sig: c_int) -> LinuxResult<isize> {
-    signal::send_signal_proc(pid, sig)
+    signal::send_siginfo_proc(
+        pid,
+        SigInfo {
+            signo: sig as u32,
+            code: SI_USER,
+            pid: current().task_ext().thread.process().pid(),
+            ..Default::default()
+        },
+    )
+}
+
+/// Queue signal `sig` with the payload in `info` for process `pid`.
+///
+/// Only the kernel may send signals with a non-negative `si_code`, or
+/// pretend to be `tkill`, unless the target is the calling process.
+pub fn sys_rt_sigqueueinfo(
+    pid: c_int,
+    sig: c_int,
+    info: UserConstPtr<QueuedSigInfo>,
+) -> LinuxResult<isize> {
+    let info = SigInfo::from(unsafe { info.get()?.read() });
+    let own_pid = current().task_ext().thread.process().pid();
+    if (info.code >= 0 || info.code == SI_TKILL) && pid as u32 != own_pid {
+        return Err(LinuxError::EPERM);
+    }
+    signal::send_siginfo_proc(
+        pid,
+        SigInfo {
+            signo: sig as u32,
+            ..info
+        },
+    )
+}
+
+/// The information about signal `sig` sent by `tkill` or `tgkill`.
+fn tkill_info(sig: c_int) -> SigInfo {
+    SigInfo {
+        signo: sig as u32,
+        code: SI_TKILL,
+        pid: current().task_ext().thread.process().pid(),
+        ..Default::default()
+    }
 }
 
 pub fn sys_tkill(tid: c_int, sig: c_int) -> LinuxResult<isize> {
-    signal::send_signal_thread(tid, sig)
+    signal::send_siginfo_thread(tid, tkill_info(sig))
 }
 
 pub fn sys_tgkill(tgid: c_int, tid: c_int, sig: c_int) -> LinuxResult<isize> {
-    signal::send_signal_thread(tid, sig)
+    signal::send_siginfo_thread_in_group(tgid, tid, tkill_info(sig))
 }
 
-pub fn sys_rt_sigtimedwait() -> LinuxResult<isize> {
-    warn!("sys_rt_sigtimedwait: I'm always waiting for you.");
-    Ok(0)
+/// Wait for one of the signals in `set` and take it without running its
+/// handler, returning its number and filling in `info` if given. This also
+/// backs `sigwaitinfo`, which passes no timeout.
+///
+/// `SIGKILL` and `SIGSTOP` are dropped from `set`, they always act. Other
+/// signals outside `set` stay pending: if one is neither blocked nor ignored
+/// the wait fails with `EINTR`, and the signal is then delivered as usual, so
+/// e.g. an unhandled `SIGTERM` still terminates the process.
+pub fn sys_rt_sigtimedwait(
+    set: UserConstPtr<u64>,
+    info: UserPtr<QueuedSigInfo>,
+    timeout: UserConstPtr<timespec>,
+    sigsetsize: usize,
+) -> LinuxResult<isize> {
+    if sigsetsize != size_of::<u64>() {
+        return Err(LinuxError::EINVAL);
+    }
+    let set = SigMask::from_sigset(unsafe { set.get()?.read() });
+    let timeout = timeout
+        .nullable(UserConstPtr::get)?
+        .map(|ts| timespec_to_duration(unsafe { ts.read() }))
+        .transpose()?;
+    let info_ptr = info.nullable(UserPtr::get)?;
+
+    let info = signal::wait_for_signal_in(set, timeout)?;
+    if let Some(info_ptr) = info_ptr {
+        unsafe { info_ptr.write(info.into()) };
+    }
+    Ok(info.signo as _)
 }
 
 pub fn sys_rt_getrlimit(resource: c_int, rlimits: UserPtr<rlimit>) -> LinuxResult<isize> {
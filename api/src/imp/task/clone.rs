@@ -168,6 +168,8 @@ pub fn sys_clone(
                 .deref_from(&process_data.ns)
                 .init_new(FD_TABLE.copy_inner());
         }
+        *process_data.cloexec_fds.lock() =
+            curr.task_ext().process_data().cloexec_fds.lock().clone();
 
         if flags.contains(CloneFlags::FS) {
             CURRENT_DIR
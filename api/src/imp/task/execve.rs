@@ -65,9 +65,13 @@ pub fn sys_execve(
         .rsplit_once('/')
         .map_or(path.as_str(), |(_, name)| name);
     curr.set_name(name);
-    *curr_ext.process_data().exe_path.write() = path;
+    // `/proc/self/exe` reports an absolute path.
+    *curr_ext.process_data().exe_path.write() = axfs::api::canonicalize(&path).unwrap_or(path);
 
-    // TODO: fd close-on-exec
+    let cloexec_fds = core::mem::take(&mut *curr_ext.process_data().cloexec_fds.lock());
+    for fd in cloexec_fds {
+        arceos_posix_api::sys_close(fd);
+    }
 
     let uctx = UspaceContext::new(entry_point.as_usize(), user_stack_base, 0);
     unsafe { uctx.enter_uspace(curr.kernel_stack_top().expect("No kernel stack top")) }
@@ -1,15 +1,102 @@
+use core::time::Duration;
+
 use arceos_posix_api as api;
-use axerrno::LinuxResult;
+use axerrno::{LinuxError, LinuxResult};
+use axtask::{AxCpuMask, TaskExtRef, current};
+use linux_raw_sys::general::{CLOCK_MONOTONIC, CLOCK_REALTIME, TIMER_ABSTIME};
+use starry_core::{signal, task::THREAD_TABLE};
 
 use crate::ptr::{PtrWrapper, UserConstPtr, UserPtr};
 
+/// Give up the CPU to the next ready task, always succeeds.
 pub fn sys_sched_yield() -> LinuxResult<isize> {
     Ok(api::sys_sched_yield() as _)
 }
 
+/// Write the CPU affinity mask of the thread `pid` (0 for the caller) to
+/// `mask` and return the number of bytes written.
+///
+/// `cpusetsize` must be a multiple of the word size and large enough to hold
+/// a bit for every CPU. Only the caller's own mask is known here, other
+/// threads report all CPUs, which they run on as affinity can not be set from
+/// user space.
+pub fn sys_sched_getaffinity(pid: i32, cpusetsize: usize, mask: UserPtr<u8>) -> LinuxResult<isize> {
+    if cpusetsize * 8 < axconfig::SMP || cpusetsize % size_of::<usize>() != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let curr = current();
+    let cpumask = if pid == 0 || pid as u32 == curr.task_ext().thread.tid() {
+        curr.cpumask()
+    } else if pid > 0 && THREAD_TABLE.read().contains_key(&(pid as u32)) {
+        AxCpuMask::full()
+    } else {
+        return Err(LinuxError::ESRCH);
+    };
+
+    let bytes = cpumask.as_bytes();
+    let len = bytes
+        .len()
+        .next_multiple_of(size_of::<usize>())
+        .min(cpusetsize);
+    let mask = mask.get_as_array(len)?;
+    unsafe {
+        mask.write_bytes(0, len);
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), mask, bytes.len().min(len));
+    }
+    Ok(len as _)
+}
+
+pub(crate) fn timespec_to_duration(ts: api::ctypes::timespec) -> LinuxResult<Duration> {
+    if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Sleep for `dur`. If a signal cuts the sleep short, the time left is
+/// written to `rem` (when given) and `EINTR` is returned.
+fn sleep(dur: Duration, rem: Option<*mut api::ctypes::timespec>) -> LinuxResult<isize> {
+    let Some(left) = signal::sleep_interruptible(dur) else {
+        return Ok(0);
+    };
+    if let Some(rem) = rem {
+        unsafe { rem.write(left.into()) };
+    }
+    Err(LinuxError::EINTR)
+}
+
 pub fn sys_nanosleep(
     req: UserConstPtr<api::ctypes::timespec>,
     rem: UserPtr<api::ctypes::timespec>,
 ) -> LinuxResult<isize> {
-    unsafe { Ok(api::sys_nanosleep(req.get()?, rem.get()?) as _) }
+    let dur = timespec_to_duration(unsafe { req.get()?.read() })?;
+    let rem = rem.nullable(UserPtr::get)?;
+    debug!("sys_nanosleep <= {:?}", dur);
+    sleep(dur, rem)
+}
+
+/// Sleep on `clock_id`, either for the relative time in `req` or, with
+/// `TIMER_ABSTIME`, until the clock reaches `req`. `rem` is only written for
+/// relative sleeps.
+pub fn sys_clock_nanosleep(
+    clock_id: i32,
+    flags: i32,
+    req: UserConstPtr<api::ctypes::timespec>,
+    rem: UserPtr<api::ctypes::timespec>,
+) -> LinuxResult<isize> {
+    let now = match clock_id as u32 {
+        CLOCK_REALTIME => axhal::time::wall_time(),
+        CLOCK_MONOTONIC => axhal::time::monotonic_time(),
+        _ => return Err(LinuxError::EINVAL),
+    };
+    let req = timespec_to_duration(unsafe { req.get()?.read() })?;
+    debug!(
+        "sys_clock_nanosleep <= clock: {}, flags: {:#x}, req: {:?}",
+        clock_id, flags, req
+    );
+    if flags as u32 & TIMER_ABSTIME != 0 {
+        return sleep(req.saturating_sub(now), None);
+    }
+    let rem = rem.nullable(UserPtr::get)?;
+    sleep(req, rem)
 }
@@ -3,13 +3,17 @@ use axtask::{TaskExtRef, current};
 use macro_rules_attribute::apply;
 use num_enum::TryFromPrimitive;
 
-use crate::syscall_instrument;
+use crate::{
+    ptr::{PtrWrapper, UserPtr},
+    syscall_instrument,
+};
 
 #[apply(syscall_instrument)]
 pub fn sys_getpid() -> LinuxResult<isize> {
     Ok(axtask::current().task_ext().thread.process().pid() as _)
 }
 
+/// The init process has no parent, for which 0 is returned.
 #[apply(syscall_instrument)]
 pub fn sys_getppid() -> LinuxResult<isize> {
     Ok(axtask::current()
@@ -17,13 +21,12 @@ pub fn sys_getppid() -> LinuxResult<isize> {
         .thread
         .process()
         .parent()
-        .unwrap()
-        .pid() as _)
+        .map_or(0, |parent| parent.pid()) as _)
 }
 
 #[apply(syscall_instrument)]
 pub fn sys_gettid() -> LinuxResult<isize> {
-    Ok(axtask::current().id().as_u64() as _)
+    Ok(axtask::current().task_ext().thread.tid() as _)
 }
 
 /// ARCH_PRCTL codes
@@ -47,16 +50,17 @@ enum ArchPrctlCode {
     SetCpuid = 0x1012,
 }
 
-/// To set the clear_child_tid field in the task extended data.
+/// Set the address whose word is cleared when the calling thread exits, see
+/// [`starry_core::task::clear_child_tid`].
 ///
-/// The set_tid_address() always succeeds
+/// The set_tid_address() always succeeds and returns the caller's tid.
 #[apply(syscall_instrument)]
-pub fn sys_set_tid_address(clear_child_tid: usize) -> LinuxResult<isize> {
+pub fn sys_set_tid_address(tidptr: UserPtr<i32>) -> LinuxResult<isize> {
     let curr = current();
     curr.task_ext()
         .thread_data()
-        .set_clear_child_tid(clear_child_tid);
-    Ok(curr.id().as_u64() as isize)
+        .set_clear_child_tid(tidptr.address().as_usize());
+    Ok(curr.task_ext().thread.tid() as _)
 }
 
 #[cfg(target_arch = "x86_64")]
@@ -66,8 +70,6 @@ pub fn sys_arch_prctl(
     code: i32,
     addr: usize,
 ) -> LinuxResult<isize> {
-    use crate::ptr::{PtrWrapper, UserPtr};
-
     let code = ArchPrctlCode::try_from(code).map_err(|_| axerrno::LinuxError::EINVAL)?;
     debug!("sys_arch_prctl: code = {:?}, addr = {:#x}", code, addr);
 
@@ -4,9 +4,11 @@ use axprocess::{Pid, Process};
 use axtask::{TaskExtRef, current};
 use bitflags::bitflags;
 use linux_raw_sys::general::{
-    __WALL, __WCLONE, __WNOTHREAD, WCONTINUED, WEXITED, WNOHANG, WNOWAIT, WUNTRACED,
+    __WALL, __WCLONE, __WNOTHREAD, CLD_DUMPED, CLD_EXITED, CLD_KILLED, P_ALL, P_PGID, P_PID,
+    SIGCHLD, WCONTINUED, WEXITED, WNOHANG, WNOWAIT, WUNTRACED,
 };
 use macro_rules_attribute::apply;
+use starry_core::signal::{self, WCOREFLAG};
 
 use crate::{
     ptr::{PtrWrapper, UserPtr},
@@ -58,6 +60,38 @@ impl WaitPid {
     }
 }
 
+/// Wait for a child selected by `pid` to exit and return it, or `None` if
+/// `WNOHANG` is given and no child has exited yet.
+///
+/// Fails with `EINTR` if a signal which is neither blocked nor ignored
+/// arrives before any child has exited. The child is reaped unless `WNOWAIT`
+/// is given.
+fn wait_child(pid: WaitPid, options: WaitOptions) -> LinuxResult<Option<Arc<Process>>> {
+    let curr = current();
+    let process = curr.task_ext().thread.process();
+
+    let children = process
+        .children()
+        .into_iter()
+        .filter(|child| pid.apply(child))
+        .collect::<Vec<_>>();
+    if children.is_empty() {
+        return Err(LinuxError::ECHILD);
+    }
+
+    let find_zombie = || children.iter().find(|child| child.is_zombie());
+    if !options.contains(WaitOptions::WNOHANG) {
+        signal::wait_interruptible(|| find_zombie().is_some())?;
+    }
+    let Some(child) = find_zombie() else {
+        return Ok(None);
+    };
+    if !options.contains(WaitOptions::WNOWAIT) {
+        child.free();
+    }
+    Ok(Some(child.clone()))
+}
+
 #[apply(syscall_instrument)]
 pub fn sys_waitpid(pid: i32, exit_code_ptr: UserPtr<i32>, options: u32) -> LinuxResult<isize> {
     let options = WaitOptions::from_bits_truncate(options);
@@ -76,30 +110,82 @@ pub fn sys_waitpid(pid: i32, exit_code_ptr: UserPtr<i32>, options: u32) -> Linux
         WaitPid::Pgid(-pid as _)
     };
 
-    let children = process
-        .children()
-        .into_iter()
-        .filter(|child| pid.apply(child))
-        .collect::<Vec<_>>();
-    if children.is_empty() {
-        return Err(LinuxError::ECHILD);
+    let exit_code = exit_code_ptr.nullable(UserPtr::get)?;
+    let Some(child) = wait_child(pid, options)? else {
+        return Ok(0);
+    };
+    if let Some(exit_code) = exit_code {
+        unsafe { exit_code.write(child.exit_code()) };
     }
+    Ok(child.pid() as _)
+}
 
-    let exit_code = exit_code_ptr.nullable(UserPtr::get)?;
-    loop {
-        if let Some(child) = children.iter().find(|child| child.is_zombie()) {
-            if !options.contains(WaitOptions::WNOWAIT) {
-                child.free();
-            }
-            if let Some(exit_code) = exit_code {
-                unsafe { exit_code.write(child.exit_code()) };
-            }
-            return Ok(child.pid() as _);
-        } else if options.contains(WaitOptions::WNOHANG) {
-            return Ok(0);
+/// The part of `siginfo_t` filled in by `waitid`.
+#[repr(C)]
+#[derive(Default)]
+pub struct WaitSigInfo {
+    signo: i32,
+    errno: i32,
+    code: i32,
+    _pad: i32,
+    pid: i32,
+    uid: u32,
+    status: i32,
+    _rest: [i32; 25],
+}
+
+impl WaitSigInfo {
+    /// Describe the exit of `child`, decoding its wait status.
+    fn from_exited(child: &Process) -> Self {
+        let exit_code = child.exit_code();
+        let (code, status) = if exit_code & 0x7f == 0 {
+            (CLD_EXITED, (exit_code >> 8) & 0xff)
+        } else if exit_code & WCOREFLAG != 0 {
+            (CLD_DUMPED, exit_code & 0x7f)
         } else {
-            // TODO: process wait queue
-            crate::sys_sched_yield()?;
+            (CLD_KILLED, exit_code & 0x7f)
+        };
+        Self {
+            signo: SIGCHLD as _,
+            code: code as _,
+            pid: child.pid() as _,
+            status,
+            ..Default::default()
         }
     }
 }
+
+#[apply(syscall_instrument)]
+pub fn sys_waitid(
+    idtype: u32,
+    id: i32,
+    infop: UserPtr<WaitSigInfo>,
+    options: u32,
+) -> LinuxResult<isize> {
+    let options = WaitOptions::from_bits_truncate(options);
+    info!(
+        "sys_waitid <= idtype: {}, id: {}, options: {:?}",
+        idtype, id, options
+    );
+
+    // Stopped and continued children are not tracked
+    if !options.contains(WaitOptions::WEXITED) {
+        return Err(LinuxError::EINVAL);
+    }
+    let pid = match idtype {
+        P_ALL => WaitPid::Any,
+        P_PID if id > 0 => WaitPid::Pid(id as _),
+        P_PGID if id > 0 => WaitPid::Pgid(id as _),
+        P_PGID if id == 0 => WaitPid::Pgid(current().task_ext().thread.process().group().pgid()),
+        _ => return Err(LinuxError::EINVAL),
+    };
+
+    let infop = infop.nullable(UserPtr::get)?;
+    let info = wait_child(pid, options)?.map_or_else(WaitSigInfo::default, |child| {
+        WaitSigInfo::from_exited(&child)
+    });
+    if let Some(infop) = infop {
+        unsafe { infop.write(info) };
+    }
+    Ok(0)
+}
@@ -1,12 +1,24 @@
 use arceos_posix_api::{self as api, ctypes::timeval};
-use axerrno::LinuxResult;
-use axhal::time::{monotonic_time_nanos, nanos_to_ticks};
-use starry_core::task::time_stat_output;
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{NANOS_PER_MICROS, TimeValue, monotonic_time_nanos, nanos_to_ticks};
+use linux_raw_sys::general::{CLOCK_MONOTONIC, CLOCK_REALTIME, ITIMER_REAL};
+use starry_core::task::{set_real_timer, time_stat_output};
 
-use crate::ptr::{PtrWrapper, UserPtr};
+use crate::ptr::{PtrWrapper, UserConstPtr, UserPtr};
 
+/// Read `CLOCK_MONOTONIC` (time since boot) or `CLOCK_REALTIME` (wall-clock
+/// time) into `tp`.
 pub fn sys_clock_gettime(clock_id: i32, tp: UserPtr<api::ctypes::timespec>) -> LinuxResult<isize> {
-    unsafe { Ok(api::sys_clock_gettime(clock_id, tp.get()?) as _) }
+    let now = match clock_id as u32 {
+        CLOCK_REALTIME => axhal::time::wall_time(),
+        CLOCK_MONOTONIC => axhal::time::monotonic_time(),
+        _ => {
+            warn!("sys_clock_gettime: unsupported clock {}", clock_id);
+            return Err(LinuxError::EINVAL);
+        }
+    };
+    unsafe { *tp.get()? = now.into() };
+    Ok(0)
 }
 
 pub fn sys_get_time_of_day(ts: UserPtr<timeval>) -> LinuxResult<isize> {
@@ -37,3 +49,67 @@ pub fn sys_times(tms: UserPtr<Tms>) -> LinuxResult<isize> {
     }
     Ok(nanos_to_ticks(monotonic_time_nanos()) as _)
 }
+
+#[repr(C)]
+pub struct ITimerVal {
+    it_interval: timeval,
+    it_value: timeval,
+}
+
+fn timeval_to_time_value(tv: &timeval) -> LinuxResult<TimeValue> {
+    if tv.tv_sec < 0 || !(0..1_000_000).contains(&tv.tv_usec) {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(TimeValue::new(
+        tv.tv_sec as u64,
+        (tv.tv_usec as u64 * NANOS_PER_MICROS) as u32,
+    ))
+}
+
+fn time_value_to_timeval(tv: TimeValue) -> timeval {
+    timeval {
+        tv_sec: tv.as_secs() as _,
+        tv_usec: tv.subsec_micros() as _,
+    }
+}
+
+/// Arm or disarm the `ITIMER_REAL` timer, which raises `SIGALRM` on expiry.
+///
+/// The virtual and profiling timers are not supported.
+pub fn sys_setitimer(
+    which: i32,
+    new_value: UserConstPtr<ITimerVal>,
+    old_value: UserPtr<ITimerVal>,
+) -> LinuxResult<isize> {
+    if which as u32 != ITIMER_REAL {
+        return Err(LinuxError::EINVAL);
+    }
+    let new_value = unsafe { &*new_value.get()? };
+    let value = timeval_to_time_value(&new_value.it_value)?;
+    let interval = timeval_to_time_value(&new_value.it_interval)?;
+    let old_value = old_value.nullable(UserPtr::get)?;
+
+    let (old, old_interval) = set_real_timer(value, interval);
+    if let Some(old_value) = old_value {
+        unsafe {
+            *old_value = ITimerVal {
+                it_interval: time_value_to_timeval(old_interval),
+                it_value: time_value_to_timeval(old),
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// Raise `SIGALRM` after `seconds`, replacing any previous alarm.
+///
+/// Returns the seconds left on the previous alarm, rounded to the nearest
+/// second but at least 1, or 0 if none was pending.
+pub fn sys_alarm(seconds: u32) -> LinuxResult<isize> {
+    let (old, _) = set_real_timer(TimeValue::from_secs(seconds as u64), TimeValue::ZERO);
+    if old.is_zero() {
+        return Ok(0);
+    }
+    let secs = old.as_secs() + (old.subsec_micros() >= 500_000) as u64;
+    Ok(secs.max(1) as _)
+}
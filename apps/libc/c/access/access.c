@@ -0,0 +1,30 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <unistd.h>
+
+int main()
+{
+    const char *path = "/access_test.txt";
+
+    int fd = open(path, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    close(fd);
+
+    if (access(path, F_OK) == 0)
+        printf("access F_OK on an existing file succeeds\n");
+    if (faccessat(AT_FDCWD, path, R_OK | W_OK, 0) == 0)
+        printf("faccessat R_OK | W_OK on a readable file succeeds\n");
+    if (faccessat(AT_FDCWD, "/access_missing.txt", F_OK, 0) < 0 && errno == ENOENT)
+        printf("faccessat on a missing file fails with ENOENT\n");
+
+    fd = open(path, O_RDONLY);
+    if (faccessat(fd, "", R_OK, AT_EMPTY_PATH) == 0)
+        printf("faccessat AT_EMPTY_PATH checks the file open as dirfd\n");
+    if (faccessat(fd, "", R_OK, 0) < 0 && errno == ENOENT)
+        printf("faccessat with an empty path fails with ENOENT\n");
+    close(fd);
+
+    unlink(path);
+    return 0;
+}
@@ -0,0 +1,46 @@
+#include <signal.h>
+#include <stdio.h>
+#include <sys/time.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t received = 0;
+
+static void handler(int sig)
+{
+    received = sig;
+}
+
+int main()
+{
+    struct sigaction sa;
+    sigset_t block;
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    sigaction(SIGALRM, &sa, NULL);
+
+    sigemptyset(&block);
+    sigaddset(&block, SIGALRM);
+    sigprocmask(SIG_BLOCK, &block, NULL);
+
+    if (alarm(5) == 0 && alarm(1) == 5)
+        printf("alarm returned remaining 5\n");
+
+    sleep(2);
+    if (received == 0)
+        printf("SIGALRM held while blocked\n");
+
+    sigprocmask(SIG_UNBLOCK, &block, NULL);
+    if (received == SIGALRM)
+        printf("SIGALRM pending after deadline\n");
+
+    received = 0;
+    struct itimerval it = { .it_value = { .tv_sec = 0, .tv_usec = 100000 } };
+    setitimer(ITIMER_REAL, &it, NULL);
+    alarm(0);
+    usleep(300000);
+    if (received == 0)
+        printf("alarm(0) cancelled the timer\n");
+    return 0;
+}
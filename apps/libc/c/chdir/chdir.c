@@ -0,0 +1,36 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+int main()
+{
+    char buf[32] = {0};
+
+    mkdir("/chdir_dir", 0755);
+    int fd = open("/chdir_dir/file.txt", O_CREAT | O_RDWR | O_TRUNC, 0644);
+    write(fd, "relative", 8);
+    close(fd);
+
+    int root = open("/", O_RDONLY | O_DIRECTORY);
+    if (chdir("/chdir_dir") == 0) {
+        fd = open("file.txt", O_RDONLY);
+        if (fd >= 0 && read(fd, buf, 8) == 8 && strcmp(buf, "relative") == 0)
+            printf("relative open after chdir reads: %s\n", buf);
+        close(fd);
+    }
+
+    if (chdir("/chdir_dir/file.txt") < 0 && errno == ENOTDIR)
+        printf("chdir to a file fails with ENOTDIR\n");
+    if (chdir("/chdir_missing") < 0 && errno == ENOENT)
+        printf("chdir to a missing directory fails with ENOENT\n");
+
+    if (fchdir(root) == 0 && getcwd(buf, sizeof(buf)) && strcmp(buf, "/") == 0)
+        printf("fchdir back to the root\n");
+
+    close(root);
+    unlink("/chdir_dir/file.txt");
+    return 0;
+}
@@ -0,0 +1,37 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+int main()
+{
+    const char *path = "/chmod_test.txt";
+    struct stat st;
+
+    int fd = open(path, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    if (fchmod(fd, 0600) == 0)
+        printf("fchmod to 0600 succeeds\n");
+    if (fstat(fd, &st) == 0 && S_ISREG(st.st_mode) && (st.st_mode & 07777) == 0600)
+        printf("fstat after fchmod reports a regular file with mode 0600\n");
+    if (fstatat(AT_FDCWD, path, &st, 0) == 0 && (st.st_mode & 07777) == 0600)
+        printf("fstatat after fchmod reports mode 0600\n");
+    if (fchmodat(AT_FDCWD, path, 0644, 0) == 0)
+        printf("fchmodat on an existing file succeeds\n");
+    if (fstatat(AT_FDCWD, path, &st, 0) == 0 && (st.st_mode & 07777) == 0644)
+        printf("fstatat after fchmodat reports mode 0644\n");
+    if (chmod("/chmod_missing.txt", 0644) == -1 && errno == ENOENT)
+        printf("chmod on a missing file fails with ENOENT\n");
+    if (fchmod(-1, 0600) == -1 && errno == EBADF)
+        printf("fchmod on a bad fd fails with EBADF\n");
+
+    int fds[2];
+    if (pipe(fds) == 0 && fchmod(fds[0], 0600) == 0)
+        printf("fchmod on a pipe succeeds\n");
+    close(fds[0]);
+    close(fds[1]);
+
+    close(fd);
+    unlink(path);
+    return 0;
+}
@@ -0,0 +1,25 @@
+#include <errno.h>
+#include <stdio.h>
+#include <time.h>
+
+static int before(const struct timespec *a, const struct timespec *b)
+{
+    return a->tv_sec < b->tv_sec || (a->tv_sec == b->tv_sec && a->tv_nsec <= b->tv_nsec);
+}
+
+int main()
+{
+    struct timespec first, second;
+
+    if (clock_gettime(CLOCK_MONOTONIC, &first) == 0 &&
+        clock_gettime(CLOCK_MONOTONIC, &second) == 0 && before(&first, &second))
+        printf("CLOCK_MONOTONIC is non-decreasing\n");
+
+    if (clock_gettime(CLOCK_REALTIME, &first) == 0 && first.tv_nsec >= 0 &&
+        first.tv_nsec < 1000000000)
+        printf("CLOCK_REALTIME returns a valid timespec\n");
+
+    if (clock_gettime(-1, &first) == -1 && errno == EINVAL)
+        printf("clock_gettime with an unsupported clock: EINVAL\n");
+    return 0;
+}
@@ -0,0 +1,34 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <unistd.h>
+
+int main()
+{
+    const char *path = "/dup_test.txt";
+
+    int fd = open(path, O_CREAT | O_RDWR | O_TRUNC | O_CLOEXEC, 0644);
+    int hole = dup(fd);
+    int above = dup(fd);
+    close(hole);
+    int lowest = dup(fd);
+    if (lowest == hole && above > hole)
+        printf("dup returns the lowest free descriptor\n");
+    if (fcntl(lowest, F_GETFD) == 0)
+        printf("dup of an O_CLOEXEC fd clears FD_CLOEXEC\n");
+
+    write(fd, "shared", 6);
+    if (lseek(lowest, 0, SEEK_CUR) == 6)
+        printf("dup shares the file offset\n");
+
+    close(above);
+    if (dup(above) == -1 && errno == EBADF)
+        printf("dup of a closed fd fails with EBADF\n");
+    if (dup(-1) == -1 && errno == EBADF)
+        printf("dup of a negative fd fails with EBADF\n");
+
+    close(lowest);
+    close(fd);
+    unlink(path);
+    return 0;
+}
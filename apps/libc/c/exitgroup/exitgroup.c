@@ -0,0 +1,78 @@
+#define _GNU_SOURCE
+#include <linux/futex.h>
+#include <sched.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#define THREAD_FLAGS \
+    (CLONE_VM | CLONE_FS | CLONE_FILES | CLONE_SIGHAND | CLONE_THREAD | CLONE_SYSVSEM | \
+     CLONE_CHILD_CLEARTID)
+
+static volatile int ctid;
+static char stack[64 * 1024];
+
+static int thread_exit(void *arg)
+{
+    (void)arg;
+    syscall(SYS_exit, 0);
+    return 0;
+}
+
+static int thread_exit_group(void *arg)
+{
+    (void)arg;
+    syscall(SYS_exit_group, 7);
+    return 0;
+}
+
+static void sleep_forever(void)
+{
+    struct timespec delay = {1, 0};
+    for (;;)
+        nanosleep(&delay, NULL);
+}
+
+static int thread_sleep(void *arg)
+{
+    (void)arg;
+    sleep_forever();
+    return 0;
+}
+
+int main()
+{
+    int status, tid;
+
+    ctid = -1;
+    if (clone(thread_exit, stack + sizeof(stack), THREAD_FLAGS, NULL, NULL, NULL, &ctid) < 0) {
+        perror("clone");
+        return 1;
+    }
+    while ((tid = ctid) != 0)
+        syscall(SYS_futex, &ctid, FUTEX_WAIT, tid, NULL);
+    printf("other threads keep running after a thread calls exit\n");
+
+    fflush(stdout);
+    pid_t pid = fork();
+    if (pid == 0) {
+        clone(thread_exit_group, stack + sizeof(stack), THREAD_FLAGS, NULL, NULL, NULL, &ctid);
+        sleep_forever();
+    }
+    waitpid(pid, &status, 0);
+    if (WIFEXITED(status) && WEXITSTATUS(status) == 7)
+        printf("exit_group from another thread ends the sleeping main thread\n");
+
+    pid = fork();
+    if (pid == 0) {
+        clone(thread_sleep, stack + sizeof(stack), THREAD_FLAGS, NULL, NULL, NULL, &ctid);
+        syscall(SYS_exit_group, 9);
+    }
+    waitpid(pid, &status, 0);
+    if (WIFEXITED(status) && WEXITSTATUS(status) == 9)
+        printf("exit_group ends the other threads with its exit code\n");
+    return 0;
+}
@@ -0,0 +1,27 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+int main()
+{
+    const char *path = "/fstat_test.txt";
+    const char data[] = "0123456789abcdef";
+    struct stat st;
+
+    int fd = open(path, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    write(fd, data, 16);
+
+    if (fstat(fd, &st) == 0 && st.st_size == 16 && S_ISREG(st.st_mode))
+        printf("fstat size 16\n");
+    if (fstatat(AT_FDCWD, path, &st, 0) == 0 && st.st_size == 16)
+        printf("fstatat size 16\n");
+    if (fstatat(fd, "", &st, AT_EMPTY_PATH) == 0 && st.st_size == 16)
+        printf("fstatat AT_EMPTY_PATH size 16\n");
+    if (fstatat(AT_FDCWD, path, &st, AT_SYMLINK_NOFOLLOW) == 0 && st.st_size == 16)
+        printf("fstatat AT_SYMLINK_NOFOLLOW size 16\n");
+
+    close(fd);
+    unlink(path);
+    return 0;
+}
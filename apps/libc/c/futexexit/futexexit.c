@@ -0,0 +1,52 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <linux/futex.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+static volatile int ctid;
+static char stack[64 * 1024];
+
+static int child(void *arg)
+{
+    struct timespec delay = {0, 100 * 1000000};
+    (void)arg;
+    nanosleep(&delay, NULL);
+    return 0;
+}
+
+int main()
+{
+    struct timespec timeout = {0, 10 * 1000000};
+    int word = 1;
+
+    if (syscall(SYS_futex, &word, FUTEX_WAIT, 2, NULL) == -1 && errno == EAGAIN)
+        printf("futex wait on a changed word: EAGAIN\n");
+    if (syscall(SYS_futex, &word, FUTEX_WAIT, 1, &timeout) == -1 && errno == ETIMEDOUT)
+        printf("futex wait timed out\n");
+    if (syscall(SYS_futex, &word, FUTEX_WAKE, 1) == 0)
+        printf("futex wake without waiters woke 0\n");
+
+    int pid = clone(child, stack + sizeof(stack),
+                    CLONE_VM | CLONE_PARENT_SETTID | CLONE_CHILD_CLEARTID | SIGCHLD, NULL,
+                    &ctid, NULL, &ctid);
+    if (pid < 0) {
+        perror("clone");
+        return 1;
+    }
+
+    int waits = 0, tid;
+    while ((tid = ctid) != 0) {
+        if (syscall(SYS_futex, &ctid, FUTEX_WAIT, tid, NULL) == 0)
+            waits++;
+    }
+    if (waits > 0)
+        printf("exiting child woke the futex waiter on its tid\n");
+    waitpid(pid, NULL, 0);
+    return 0;
+}
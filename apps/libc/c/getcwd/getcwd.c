@@ -0,0 +1,23 @@
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+int main()
+{
+    char buf[64];
+
+    if (getcwd(buf, sizeof(buf)) && strcmp(buf, "/") == 0)
+        printf("getcwd at the root: /\n");
+
+    mkdir("/getcwd_dir", 0755);
+    chdir("/getcwd_dir");
+    if (getcwd(buf, sizeof(buf)))
+        printf("getcwd after chdir: %s\n", buf);
+    if (getcwd(buf, 4) == NULL && errno == ERANGE)
+        printf("getcwd with a short buffer fails with ERANGE\n");
+
+    chdir("/");
+    return 0;
+}
@@ -0,0 +1,23 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main()
+{
+    pid_t parent = getpid();
+    if (syscall(SYS_gettid) == parent)
+        printf("main thread id equals pid\n");
+
+    pid_t pid = fork();
+    if (pid == 0) {
+        if (getppid() == parent)
+            printf("child getppid equals parent getpid\n");
+        if (getpid() != parent)
+            printf("child has its own pid\n");
+        exit(0);
+    }
+    waitpid(pid, NULL, 0);
+    return 0;
+}
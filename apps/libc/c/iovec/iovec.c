@@ -0,0 +1,34 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <limits.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/uio.h>
+#include <unistd.h>
+
+int main()
+{
+    int fds[2];
+    char head[6] = {0}, tail[7] = {0};
+    struct iovec out[2] = {{"hello ", 6}, {"world!", 6}};
+    struct iovec in[2] = {{head, 6}, {tail, 6}};
+
+    pipe(fds);
+    if (writev(fds[1], out, 2) == 12 && readv(fds[0], in, 2) == 12)
+        printf("readv/writev round trip: %s%s\n", head, tail);
+
+    if (writev(fds[1], out, -1) == -1 && errno == EINVAL)
+        printf("writev with a negative count: EINVAL\n");
+    if (readv(fds[0], in, -1) == -1 && errno == EINVAL)
+        printf("readv with a negative count: EINVAL\n");
+    if (writev(fds[1], out, IOV_MAX + 1) == -1 && errno == EINVAL)
+        printf("writev with more than IOV_MAX segments: EINVAL\n");
+    if (readv(fds[0], in, IOV_MAX + 1) == -1 && errno == EINVAL)
+        printf("readv with more than IOV_MAX segments: EINVAL\n");
+
+    struct iovec huge[2] = {{head, SIZE_MAX / 2}, {tail, SIZE_MAX / 2}};
+    if (writev(fds[1], huge, 2) == -1 && errno == EINVAL)
+        printf("writev with lengths overflowing ssize_t: EINVAL\n");
+    return 0;
+}
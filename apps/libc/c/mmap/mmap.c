@@ -0,0 +1,40 @@
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+int main()
+{
+    long page = sysconf(_SC_PAGESIZE);
+    char *p = mmap(NULL, page * 3, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    if (p == MAP_FAILED) {
+        printf("mmap failed\n");
+        return 1;
+    }
+    if (((long)p & (page - 1)) == 0 && p[0] == 0 && p[page * 3 - 1] == 0)
+        printf("anonymous mapping is page aligned and zero-filled\n");
+
+    memset(p, 'a', page * 3);
+    if (p[page] == 'a' && p[page * 3 - 1] == 'a')
+        printf("anonymous mapping is writable\n");
+
+    if (munmap(p, page * 3) == 0)
+        printf("munmap returns 0\n");
+
+    if (mmap(NULL, 0, PROT_READ, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0) == MAP_FAILED &&
+        errno == EINVAL)
+        printf("mmap with zero length: EINVAL\n");
+    if (mmap(NULL, page, PROT_READ, MAP_ANONYMOUS, -1, 0) == MAP_FAILED && errno == EINVAL)
+        printf("mmap without MAP_PRIVATE or MAP_SHARED: EINVAL\n");
+    if (mmap(p + 1, page, PROT_READ, MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED, -1, 0) ==
+            MAP_FAILED &&
+        errno == EINVAL)
+        printf("mmap with a misaligned MAP_FIXED address: EINVAL\n");
+    if (mmap(NULL, page, PROT_READ, MAP_PRIVATE | MAP_ANONYMOUS, -1, 1) == MAP_FAILED &&
+        errno == EINVAL)
+        printf("mmap with a misaligned offset: EINVAL\n");
+    if (munmap(p + 1, page) == -1 && errno == EINVAL)
+        printf("munmap with a misaligned address: EINVAL\n");
+    return 0;
+}
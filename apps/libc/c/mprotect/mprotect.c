@@ -0,0 +1,50 @@
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main()
+{
+    long page = sysconf(_SC_PAGESIZE);
+    char *p = mmap(NULL, page * 2, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    if (p == MAP_FAILED) {
+        printf("mmap failed\n");
+        return 1;
+    }
+    p[0] = 'x';
+
+    if (mprotect(p + 1, page, PROT_READ) == -1 && errno == EINVAL)
+        printf("mprotect misaligned addr: EINVAL\n");
+    if (madvise(p, page, MADV_WILLNEED) == 0)
+        printf("madvise returns 0\n");
+    if (madvise(p, page, 12345) == -1 && errno == EINVAL)
+        printf("madvise unknown advice: EINVAL\n");
+    if (madvise(p + 1, page, MADV_NORMAL) == -1 && errno == EINVAL)
+        printf("madvise misaligned addr: EINVAL\n");
+
+    munmap(p + page, page);
+    if (mprotect(p, page * 2, PROT_READ) == -1 && errno == ENOMEM)
+        printf("mprotect unmapped range: ENOMEM\n");
+
+    if (mprotect(p, page, PROT_READ) == 0 && p[0] == 'x')
+        printf("mprotect read-only keeps contents readable\n");
+
+    pid_t pid = fork();
+    if (pid == 0) {
+        p[0] = 'y';
+        _exit(0);
+    }
+    int status;
+    waitpid(pid, &status, 0);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGSEGV)
+        printf("write to read-only page faults with SIGSEGV\n");
+
+    if (mprotect(p, page, PROT_READ | PROT_WRITE) == 0) {
+        p[0] = 'z';
+        printf("mprotect read-write allows writes again: %c\n", p[0]);
+    }
+    munmap(p, page);
+    return 0;
+}
@@ -0,0 +1,54 @@
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+
+static long elapsed_ms(const struct timespec *start)
+{
+    struct timespec now;
+    clock_gettime(CLOCK_MONOTONIC, &now);
+    return (now.tv_sec - start->tv_sec) * 1000 + (now.tv_nsec - start->tv_nsec) / 1000000;
+}
+
+static void on_alarm(int sig)
+{
+    (void)sig;
+}
+
+int main()
+{
+    struct timespec start, req = {0, 200 * 1000000}, rem;
+
+    clock_gettime(CLOCK_MONOTONIC, &start);
+    if (nanosleep(&req, NULL) == 0 && elapsed_ms(&start) >= 200)
+        printf("nanosleep slept at least 200ms\n");
+
+    req.tv_nsec = 1000000000;
+    if (nanosleep(&req, NULL) == -1 && errno == EINVAL)
+        printf("nanosleep with invalid tv_nsec: EINVAL\n");
+
+    clock_gettime(CLOCK_MONOTONIC, &start);
+    struct timespec deadline = start;
+    deadline.tv_nsec += 100 * 1000000;
+    if (deadline.tv_nsec >= 1000000000) {
+        deadline.tv_sec += 1;
+        deadline.tv_nsec -= 1000000000;
+    }
+    if (clock_nanosleep(CLOCK_MONOTONIC, TIMER_ABSTIME, &deadline, NULL) == 0 &&
+        elapsed_ms(&start) >= 100)
+        printf("clock_nanosleep reached the absolute deadline\n");
+
+    struct sigaction sa;
+    memset(&sa, 0, sizeof(sa));
+    sa.sa_handler = on_alarm;
+    sa.sa_flags = SA_RESTART;
+    sigaction(SIGALRM, &sa, NULL);
+    alarm(1);
+    req.tv_sec = 3;
+    req.tv_nsec = 0;
+    if (nanosleep(&req, &rem) == -1 && errno == EINTR && rem.tv_sec >= 1 && rem.tv_sec < 3)
+        printf("nanosleep interrupted by a signal reports the time left\n");
+    return 0;
+}
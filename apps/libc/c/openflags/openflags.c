@@ -0,0 +1,43 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main()
+{
+    const char *path = "/openflags_test.txt";
+
+    int fd = open(path, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    close(fd);
+
+    if (open(path, O_RDONLY | O_DIRECTORY) < 0 && errno == ENOTDIR)
+        printf("O_DIRECTORY on a file fails with ENOTDIR\n");
+    if (open(path, O_CREAT | O_EXCL | O_RDWR, 0644) < 0 && errno == EEXIST)
+        printf("O_CREAT | O_EXCL on an existing file fails with EEXIST\n");
+
+    fd = open(path, O_RDONLY | O_CLOEXEC);
+    if (fd >= 0 && (fcntl(fd, F_GETFD) & FD_CLOEXEC))
+        printf("O_CLOEXEC sets FD_CLOEXEC\n");
+    int dup_fd = dup(fd);
+    if (dup_fd >= 0 && !(fcntl(dup_fd, F_GETFD) & FD_CLOEXEC))
+        printf("dup clears FD_CLOEXEC\n");
+
+    close(dup_fd);
+    close(fd);
+    unlink(path);
+
+    const char *race_path = "/openflags_race.txt";
+    fflush(stdout);
+    for (int i = 0; i < 4; i++) {
+        if (fork() == 0)
+            _exit(open(race_path, O_CREAT | O_EXCL | O_RDWR, 0644) >= 0);
+    }
+    int created = 0, status;
+    while (wait(&status) > 0)
+        created += WIFEXITED(status) && WEXITSTATUS(status) == 1;
+    if (created == 1)
+        printf("O_CREAT | O_EXCL succeeds exactly once\n");
+    unlink(race_path);
+    return 0;
+}
@@ -0,0 +1,27 @@
+#define _GNU_SOURCE
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+int main()
+{
+    int fds[2];
+    const char msg[] = "through the pipe";
+    char buf[sizeof(msg)] = {0};
+
+    if (pipe2(fds, O_CLOEXEC) != 0) {
+        printf("pipe2 failed\n");
+        return 1;
+    }
+    if ((fcntl(fds[0], F_GETFD) & FD_CLOEXEC) && (fcntl(fds[1], F_GETFD) & FD_CLOEXEC))
+        printf("pipe2 O_CLOEXEC sets FD_CLOEXEC on both ends\n");
+
+    write(fds[1], msg, sizeof(msg));
+    if (read(fds[0], buf, sizeof(msg)) == sizeof(msg) && strcmp(buf, msg) == 0)
+        printf("pipe2 read back: %s\n", buf);
+
+    close(fds[0]);
+    close(fds[1]);
+    return 0;
+}
@@ -0,0 +1,62 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+static void on_usr1(int sig)
+{
+    (void)sig;
+}
+
+int main()
+{
+    int fds[2];
+    struct timespec timeout = {0, 50 * 1000000};
+    pipe(fds);
+
+    struct pollfd pfd = {fds[0], POLLIN, 0};
+    if (ppoll(&pfd, 1, &timeout, NULL) == 0 && pfd.revents == 0)
+        printf("ppoll on an empty pipe timed out\n");
+
+    pid_t pid = fork();
+    if (pid == 0) {
+        struct timespec delay = {0, 100 * 1000000};
+        nanosleep(&delay, NULL);
+        write(fds[1], "x", 1);
+        _exit(0);
+    }
+    if (ppoll(&pfd, 1, NULL, NULL) == 1 && (pfd.revents & POLLIN))
+        printf("ppoll reports the pipe readable after a write\n");
+    waitpid(pid, NULL, 0);
+
+    struct pollfd out = {fds[1], POLLOUT, 0};
+    if (ppoll(&out, 1, &timeout, NULL) == 1 && out.revents == POLLOUT)
+        printf("ppoll reports the write end writable\n");
+
+    struct pollfd bad = {100, POLLIN, 0};
+    if (ppoll(&bad, 1, &timeout, NULL) == 1 && bad.revents == POLLNVAL)
+        printf("ppoll on a closed fd reports POLLNVAL\n");
+
+    // SIGUSR1 is blocked but the mask given to ppoll unblocks it.
+    struct sigaction sa;
+    memset(&sa, 0, sizeof(sa));
+    sa.sa_handler = on_usr1;
+    sigaction(SIGUSR1, &sa, NULL);
+    sigset_t block, empty;
+    sigemptyset(&block);
+    sigaddset(&block, SIGUSR1);
+    sigemptyset(&empty);
+    sigprocmask(SIG_BLOCK, &block, NULL);
+    kill(getpid(), SIGUSR1);
+    struct pollfd empty_pipe = {fds[0], POLLIN, 0};
+    char c;
+    read(fds[0], &c, 1);
+    if (ppoll(&empty_pipe, 1, NULL, &empty) == -1 && errno == EINTR)
+        printf("ppoll with a signal mask is interrupted by SIGUSR1\n");
+    return 0;
+}
@@ -0,0 +1,30 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+int main()
+{
+    const char *path = "/readlink_test.txt";
+    char buf[256];
+
+    ssize_t len = readlink("/proc/self/exe", buf, sizeof(buf) - 1);
+    if (len > 0) {
+        buf[len] = '\0';
+        const char *name = strrchr(buf, '/');
+        if (buf[0] == '/' && name && strcmp(name, "/readlink_c") == 0)
+            printf("/proc/self/exe points to the executable\n");
+    }
+    if (readlink("/proc/self/exe", buf, 1) == 1 && buf[0] == '/')
+        printf("readlink truncates to the buffer size\n");
+
+    int fd = open(path, O_CREAT | O_RDWR | O_TRUNC, 0644);
+    close(fd);
+    if (readlinkat(AT_FDCWD, path, buf, sizeof(buf)) == -1 && errno == EINVAL)
+        printf("readlinkat on a regular file fails with EINVAL\n");
+    unlink(path);
+    if (readlink("/readlink_missing.txt", buf, sizeof(buf)) == -1 && errno == ENOENT)
+        printf("readlink on a missing file fails with ENOENT\n");
+    return 0;
+}
@@ -0,0 +1,51 @@
+#include <signal.h>
+#include <stdio.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t received = 0;
+
+static void handler(int sig)
+{
+    received = sig;
+}
+
+int main()
+{
+    struct sigaction sa;
+    sigset_t block, cur;
+    int sig = SIGRTMIN + 1;
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    if (sigaction(sig, &sa, NULL) == 0)
+        printf("real-time handler installed\n");
+
+    sigemptyset(&block);
+    sigaddset(&block, sig);
+    sigprocmask(SIG_BLOCK, &block, NULL);
+    sigprocmask(SIG_BLOCK, NULL, &cur);
+    if (sigismember(&cur, sig))
+        printf("SIGRTMIN+1 blocked\n");
+
+    kill(getpid(), sig);
+    if (received == 0)
+        printf("SIGRTMIN+1 stays pending while blocked\n");
+
+    sigprocmask(SIG_UNBLOCK, &block, NULL);
+    if (received == sig)
+        printf("SIGRTMIN+1 delivered after unblock\n");
+
+    received = 0;
+    sigprocmask(SIG_BLOCK, &block, NULL);
+    if (raise(sig) == 0 && received == 0)
+        printf("raise queues SIGRTMIN+1 while blocked\n");
+    sigemptyset(&cur);
+    if (sigpending(&cur) == 0 && sigismember(&cur, sig))
+        printf("sigpending reports SIGRTMIN+1\n");
+
+    sigprocmask(SIG_UNBLOCK, &block, NULL);
+    if (received == sig)
+        printf("raised SIGRTMIN+1 delivered after unblock\n");
+    return 0;
+}
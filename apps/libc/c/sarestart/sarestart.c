@@ -0,0 +1,43 @@
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t received = 0;
+
+static void handler(int sig)
+{
+    received = sig;
+}
+
+int main()
+{
+    struct sigaction sa;
+    int fds[2];
+    char buf[8];
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = SA_RESTART;
+    sigaction(SIGUSR1, &sa, NULL);
+
+    pipe(fds);
+    pid_t parent = getpid();
+    pid_t pid = fork();
+    if (pid == 0) {
+        close(fds[0]);
+        kill(parent, SIGUSR1);
+        sleep(1);
+        write(fds[1], "restart", 8);
+        exit(0);
+    }
+
+    close(fds[1]);
+    ssize_t n = read(fds[0], buf, sizeof(buf));
+    if (n == 8 && received == SIGUSR1)
+        printf("read restarted after SIGUSR1: %s\n", buf);
+
+    waitpid(pid, NULL, 0);
+    return 0;
+}
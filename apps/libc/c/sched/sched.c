@@ -0,0 +1,21 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <sched.h>
+#include <stdio.h>
+
+int main()
+{
+    if (sched_yield() == 0)
+        printf("sched_yield returns 0\n");
+
+    cpu_set_t set;
+    CPU_ZERO(&set);
+    if (sched_getaffinity(0, sizeof(set), &set) == 0 && CPU_COUNT(&set) >= 1)
+        printf("sched_getaffinity reports at least one CPU\n");
+
+    if (sched_getaffinity(0, 1, &set) == -1 && errno == EINVAL)
+        printf("sched_getaffinity with a short cpusetsize: EINVAL\n");
+    if (sched_getaffinity(99999, sizeof(set), &set) == -1 && errno == ESRCH)
+        printf("sched_getaffinity of a missing thread: ESRCH\n");
+    return 0;
+}
@@ -0,0 +1,35 @@
+#define _GNU_SOURCE
+#include <sched.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile int child_tid_word = -1;
+static volatile int returned_own_tid;
+static char stack[64 * 1024];
+
+static int child(void *arg)
+{
+    (void)arg;
+    child_tid_word = syscall(SYS_gettid);
+    returned_own_tid = syscall(SYS_set_tid_address, &child_tid_word) == child_tid_word;
+    return 0;
+}
+
+int main()
+{
+    int pid = clone(child, stack + sizeof(stack), CLONE_VM | SIGCHLD, NULL);
+    if (pid < 0) {
+        perror("clone");
+        return 1;
+    }
+    waitpid(pid, NULL, 0);
+
+    if (returned_own_tid)
+        printf("set_tid_address returned the caller's tid\n");
+    if (child_tid_word == 0)
+        printf("exit cleared the clear_child_tid word\n");
+    return 0;
+}
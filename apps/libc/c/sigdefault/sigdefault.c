@@ -0,0 +1,24 @@
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main()
+{
+    int status;
+
+    kill(getpid(), SIGCHLD);
+    kill(getpid(), SIGURG);
+    printf("SIGCHLD ignored by default\n");
+
+    pid_t pid = fork();
+    if (pid == 0) {
+        kill(getpid(), SIGKILL);
+        exit(0);
+    }
+    waitpid(pid, &status, 0);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL)
+        printf("SIGKILL terminates by default\n");
+    return 0;
+}
@@ -0,0 +1,58 @@
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile int delivered = 0;
+
+static void handler(int sig)
+{
+    delivered++;
+}
+
+int main()
+{
+    struct sigaction sa;
+    sigset_t set;
+    int status;
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    sigaction(SIGUSR1, &sa, NULL);
+
+    sigemptyset(&set);
+    sigaddset(&set, SIGUSR1);
+    sigprocmask(SIG_BLOCK, &set, NULL);
+    kill(getpid(), SIGUSR1);
+    if (delivered == 0)
+        printf("blocked SIGUSR1 stays pending\n");
+
+    sigprocmask(SIG_UNBLOCK, &set, NULL);
+    if (delivered == 1)
+        printf("unblocking delivers the pending SIGUSR1\n");
+
+    signal(SIGUSR2, SIG_IGN);
+    kill(getpid(), SIGUSR2);
+    sigaction(SIGUSR2, &sa, NULL);
+    if (delivered == 1)
+        printf("ignored SIGUSR2 is dropped\n");
+
+    fflush(stdout);
+    pid_t pid = fork();
+    if (pid == 0) {
+        sigemptyset(&set);
+        sigaddset(&set, SIGTERM);
+        sigprocmask(SIG_BLOCK, &set, NULL);
+        kill(getpid(), SIGTERM);
+        printf("blocked SIGTERM does not terminate\n");
+        fflush(stdout);
+        sigprocmask(SIG_UNBLOCK, &set, NULL);
+        exit(0);
+    }
+    waitpid(pid, &status, 0);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGTERM)
+        printf("unblocked SIGTERM terminates by default\n");
+    return 0;
+}
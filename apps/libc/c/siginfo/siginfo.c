@@ -0,0 +1,80 @@
+#include <signal.h>
+#include <stdio.h>
+#include <sys/time.h>
+#include <ucontext.h>
+#include <unistd.h>
+
+static volatile int info_signo, info_code, info_pid, info_value, mask_saved;
+static volatile sig_atomic_t alarmed = 0;
+
+static void info_handler(int sig, siginfo_t *info, void *ucontext)
+{
+    ucontext_t *uc = ucontext;
+    info_signo = info->si_signo;
+    info_code = info->si_code;
+    info_pid = info->si_pid;
+    info_value = info->si_value.sival_int;
+    mask_saved = sigismember(&uc->uc_sigmask, SIGUSR2);
+}
+
+static void once_handler(int sig)
+{
+}
+
+static void fp_handler(int sig)
+{
+    volatile double z = 12345.678;
+    for (int i = 0; i < 100; i++)
+        z = z * 1.01 + 3.3;
+    alarmed = 1;
+}
+
+int main()
+{
+    struct sigaction sa, old;
+    sigset_t set;
+
+    sa.sa_sigaction = info_handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = SA_SIGINFO;
+    sigaction(SIGUSR1, &sa, NULL);
+
+    sigemptyset(&set);
+    sigaddset(&set, SIGUSR2);
+    sigprocmask(SIG_BLOCK, &set, NULL);
+    kill(getpid(), SIGUSR1);
+    if (info_signo == SIGUSR1 && info_code == SI_USER && info_pid == getpid())
+        printf("SA_SIGINFO handler gets the siginfo\n");
+    if (mask_saved)
+        printf("SA_SIGINFO handler gets the ucontext\n");
+    sigprocmask(SIG_UNBLOCK, &set, NULL);
+
+    union sigval value = {.sival_int = 42};
+    sigqueue(getpid(), SIGUSR1, value);
+    if (info_code == SI_QUEUE && info_value == 42)
+        printf("SA_SIGINFO handler gets the sigqueue payload\n");
+
+    sa.sa_handler = once_handler;
+    sa.sa_flags = SA_RESETHAND;
+    sigaction(SIGUSR2, &sa, NULL);
+    kill(getpid(), SIGUSR2);
+    sigaction(SIGUSR2, NULL, &old);
+    if (old.sa_handler == SIG_DFL)
+        printf("SA_RESETHAND resets the handler\n");
+
+    sa.sa_handler = fp_handler;
+    sa.sa_flags = 0;
+    sigaction(SIGALRM, &sa, NULL);
+    struct itimerval timer = {.it_value = {.tv_usec = 10000}};
+    setitimer(ITIMER_REAL, &timer, NULL);
+    double a = 0, b = 0;
+    long n = 0;
+    while (!alarmed) {
+        a += 1.0;
+        b += 2.0;
+        n++;
+    }
+    if (a == n && b == 2.0 * n)
+        printf("FP registers survive a signal handler\n");
+    return 0;
+}
@@ -0,0 +1,44 @@
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static void handler(int sig)
+{
+}
+
+int main()
+{
+    struct sigaction sa;
+    sigset_t all;
+    int fds[2];
+    char c;
+    int status;
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    if (sigaction(SIGKILL, &sa, NULL) == -1 && errno == EINVAL)
+        printf("SIGKILL can not be caught\n");
+    sa.sa_handler = SIG_IGN;
+    if (sigaction(SIGSTOP, &sa, NULL) == -1 && errno == EINVAL)
+        printf("SIGSTOP can not be ignored\n");
+
+    pipe(fds);
+    pid_t pid = fork();
+    if (pid == 0) {
+        sigfillset(&all);
+        sigprocmask(SIG_BLOCK, &all, NULL);
+        write(fds[1], "x", 1);
+        for (;;)
+            ;
+    }
+
+    read(fds[0], &c, 1);
+    kill(pid, SIGKILL);
+    waitpid(pid, &status, 0);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL)
+        printf("blocked SIGKILL still terminates\n");
+    return 0;
+}
@@ -0,0 +1,42 @@
+#include <signal.h>
+#include <stdio.h>
+#include <unistd.h>
+
+static volatile int usr1_blocked = 0;
+static volatile int usr2_blocked = 0;
+
+static void handler(int sig)
+{
+    sigset_t cur;
+    sigprocmask(SIG_BLOCK, NULL, &cur);
+    usr1_blocked = sigismember(&cur, SIGUSR1);
+    usr2_blocked = sigismember(&cur, SIGUSR2);
+}
+
+int main()
+{
+    struct sigaction sa;
+    sigset_t cur;
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sigaddset(&sa.sa_mask, SIGUSR2);
+    sa.sa_flags = 0;
+    sigaction(SIGUSR1, &sa, NULL);
+
+    kill(getpid(), SIGUSR1);
+    if (usr1_blocked && usr2_blocked)
+        printf("handler blocks sa_mask and the delivered signal\n");
+
+    sigprocmask(SIG_BLOCK, NULL, &cur);
+    if (!sigismember(&cur, SIGUSR1) && !sigismember(&cur, SIGUSR2))
+        printf("mask restored after handler\n");
+
+    sa.sa_flags = SA_NODEFER;
+    sigaction(SIGUSR1, &sa, NULL);
+    kill(getpid(), SIGUSR1);
+    if (!usr1_blocked && usr2_blocked)
+        printf("SA_NODEFER leaves the delivered signal unblocked\n");
+
+    return 0;
+}
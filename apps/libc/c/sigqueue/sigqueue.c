@@ -0,0 +1,42 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/syscall.h>
+#include <time.h>
+#include <unistd.h>
+
+int main()
+{
+    int sig = SIGRTMIN + 2;
+    sigset_t set;
+    sigemptyset(&set);
+    sigaddset(&set, sig);
+    sigprocmask(SIG_BLOCK, &set, NULL);
+
+    union sigval value;
+    value.sival_int = 42;
+    if (sigqueue(getpid(), sig, value) == 0)
+        printf("sigqueue queued SIGRTMIN+2\n");
+
+    siginfo_t info;
+    if (sigtimedwait(&set, &info, NULL) == sig && info.si_code == SI_QUEUE &&
+        info.si_pid == getpid() && info.si_value.sival_int == 42)
+        printf("sigtimedwait returned the payload: %d\n", info.si_value.sival_int);
+
+    struct timespec zero = {0, 0};
+    if (sigtimedwait(&set, &info, &zero) == -1 && errno == EAGAIN)
+        printf("sigtimedwait with nothing pending: EAGAIN\n");
+
+    if (raise(sig) == 0 && sigtimedwait(&set, &info, NULL) == sig &&
+        info.si_code == SI_TKILL && info.si_pid == getpid())
+        printf("raise reports SI_TKILL and the sender\n");
+
+    memset(&info, 0, sizeof(info));
+    info.si_signo = SIGUSR1;
+    info.si_code = SI_USER;
+    if (syscall(SYS_rt_sigqueueinfo, getppid(), SIGUSR1, &info) == -1 && errno == EPERM)
+        printf("rt_sigqueueinfo with a kernel si_code: EPERM\n");
+    return 0;
+}
@@ -0,0 +1,48 @@
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t received = 0;
+
+static void handler(int sig)
+{
+    received = sig;
+}
+
+int main()
+{
+    struct sigaction sa;
+    sigset_t block, old, wait_mask, cur;
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    sigaction(SIGUSR1, &sa, NULL);
+
+    sigemptyset(&block);
+    sigaddset(&block, SIGUSR1);
+    sigprocmask(SIG_BLOCK, &block, &old);
+
+    pid_t parent = getpid();
+    pid_t pid = fork();
+    if (pid == 0) {
+        kill(parent, SIGUSR1);
+        exit(0);
+    }
+
+    wait_mask = old;
+    sigdelset(&wait_mask, SIGUSR1);
+    int ret = sigsuspend(&wait_mask);
+    if (ret == -1 && errno == EINTR && received == SIGUSR1)
+        printf("sigsuspend woken by SIGUSR1\n");
+
+    sigprocmask(SIG_BLOCK, NULL, &cur);
+    if (sigismember(&cur, SIGUSR1))
+        printf("sigsuspend restored mask\n");
+
+    waitpid(pid, NULL, 0);
+    return 0;
+}
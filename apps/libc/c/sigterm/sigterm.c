@@ -0,0 +1,29 @@
+#include <signal.h>
+#include <stdio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main()
+{
+    int fds[2];
+    char c;
+    int status;
+
+    kill(getpid(), SIGCONT);
+    printf("survived default SIGCONT\n");
+
+    pipe(fds);
+    pid_t pid = fork();
+    if (pid == 0) {
+        write(fds[1], "x", 1);
+        for (;;)
+            ;
+    }
+
+    read(fds[0], &c, 1);
+    kill(pid, SIGTERM);
+    waitpid(pid, &status, 0);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGTERM)
+        printf("default SIGTERM terminates\n");
+    return 0;
+}
@@ -0,0 +1,56 @@
+#include <signal.h>
+#include <stdio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+/* Fork a child waiting for `set` and send it `sig`. */
+static int wait_in_child(sigset_t *set, int sig)
+{
+    fflush(stdout);
+    pid_t pid = fork();
+    if (pid == 0) {
+        siginfo_t info;
+        int signo = sigwaitinfo(set, &info);
+        _exit(signo == SIGUSR1 && info.si_signo == SIGUSR1 ? 0 : 1);
+    }
+    usleep(100000);
+    kill(pid, sig);
+    int status;
+    waitpid(pid, &status, 0);
+    return status;
+}
+
+int main()
+{
+    sigset_t set;
+    sigemptyset(&set);
+    sigaddset(&set, SIGUSR1);
+    sigprocmask(SIG_BLOCK, &set, NULL);
+
+    int status = wait_in_child(&set, SIGUSR1);
+    if (WIFEXITED(status) && WEXITSTATUS(status) == 0)
+        printf("sigwaitinfo took the awaited SIGUSR1\n");
+
+    status = wait_in_child(&set, SIGTERM);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGTERM)
+        printf("SIGTERM outside the set still terminates\n");
+
+    sigset_t with_kill = set;
+    sigaddset(&with_kill, SIGKILL);
+    status = wait_in_child(&with_kill, SIGKILL);
+    if (WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL)
+        printf("SIGKILL can not be waited for\n");
+
+    sigset_t both = set;
+    sigaddset(&both, SIGUSR2);
+    sigprocmask(SIG_BLOCK, &both, NULL);
+    kill(getpid(), SIGUSR2);
+    kill(getpid(), SIGUSR1);
+    sigset_t usr2;
+    sigemptyset(&usr2);
+    sigaddset(&usr2, SIGUSR2);
+    struct timespec zero = {0, 0};
+    if (sigwaitinfo(&set, NULL) == SIGUSR1 && sigtimedwait(&usr2, NULL, &zero) == SIGUSR2)
+        printf("blocked SIGUSR2 outside the set stays pending\n");
+    return 0;
+}
@@ -0,0 +1,60 @@
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t received = 0;
+
+static void handler(int sig)
+{
+    received = sig;
+}
+
+int main()
+{
+    struct sigaction sa;
+    pid_t pid = getpid();
+    pid_t tid = syscall(SYS_gettid);
+
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    sigaction(SIGUSR1, &sa, NULL);
+
+    if (syscall(SYS_tgkill, pid, tid, SIGUSR1) == 0 && received == SIGUSR1)
+        printf("tgkill matching tgid delivered\n");
+
+    fflush(stdout);
+    pid_t child = fork();
+    if (child == 0) {
+        sleep(1);
+        exit(0);
+    }
+    received = 0;
+    if (syscall(SYS_tgkill, pid, child, SIGUSR1) == -1 && errno == ESRCH && received == 0)
+        printf("tgkill wrong tgid fails with ESRCH\n");
+
+    if (syscall(SYS_tgkill, pid, tid, 0) == 0 && received == 0)
+        printf("tgkill signal 0 probes the thread\n");
+
+    received = 0;
+    if (syscall(SYS_tkill, tid, SIGUSR1) == 0 && received == SIGUSR1)
+        printf("tkill delivers the given signal\n");
+
+    sigset_t block;
+    sigemptyset(&block);
+    sigaddset(&block, SIGUSR1);
+    sigprocmask(SIG_BLOCK, &block, NULL);
+    received = 0;
+    if (syscall(SYS_tgkill, pid, tid, SIGUSR1) == 0 && received == 0)
+        printf("tgkill queues a blocked signal\n");
+    sigprocmask(SIG_UNBLOCK, &block, NULL);
+    if (received == SIGUSR1)
+        printf("tgkill blocked signal delivered after unblock\n");
+
+    waitpid(child, NULL, 0);
+    return 0;
+}
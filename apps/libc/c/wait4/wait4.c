@@ -0,0 +1,28 @@
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+int main()
+{
+    int status;
+    pid_t pid = fork();
+    if (pid == 0)
+        exit(42);
+    if (wait4(pid, &status, 0, NULL) == pid && WIFEXITED(status) && WEXITSTATUS(status) == 42)
+        printf("wait4 reaped child with exit status 42\n");
+
+    pid = fork();
+    if (pid == 0) {
+        kill(getpid(), SIGTERM);
+        for (;;)
+            pause();
+    }
+    siginfo_t info;
+    if (waitid(P_PID, pid, &info, WEXITED) == 0 && info.si_pid == pid &&
+        info.si_code == CLD_KILLED && info.si_status == SIGTERM)
+        printf("waitid reports child killed by SIGTERM\n");
+    return 0;
+}
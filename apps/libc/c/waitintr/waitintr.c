@@ -0,0 +1,37 @@
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t alarmed = 0;
+
+static void handler(int sig)
+{
+    alarmed = sig;
+}
+
+int main()
+{
+    struct sigaction sa;
+    sa.sa_handler = handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+    sigaction(SIGALRM, &sa, NULL);
+
+    fflush(stdout);
+    pid_t child = fork();
+    if (child == 0) {
+        sleep(2);
+        _exit(7);
+    }
+
+    alarm(1);
+    int status;
+    if (waitpid(child, &status, 0) == -1 && errno == EINTR && alarmed == SIGALRM)
+        printf("SIGALRM interrupts a blocking waitpid with EINTR\n");
+
+    if (waitpid(child, &status, 0) == child && WIFEXITED(status) && WEXITSTATUS(status) == 7)
+        printf("waitpid after the interruption reaps the child\n");
+    return 0;
+}
@@ -1,25 +1,36 @@
-use axtask::{TaskExtRef, exit};
-use super::signal::{send_signal_thread, Signal};
+//! Thread and process exit.
 
+use axtask::TaskExtRef;
+
+use super::signal::{Signal, send_signal_thread};
+use super::task::{clear_child_tid, notify_parent_exit};
+
+/// Exits the current thread with `exit_code`, which is already encoded as a
+/// wait status.
+///
+/// The `clear_child_tid` futex is woken and, when this is the last thread,
+/// the parent is notified. With `group_exit` the other threads of the
+/// process are killed as well.
 pub fn do_exit(exit_code: i32, group_exit: bool) -> ! {
     let curr = axtask::current();
-    let thr = &curr.task_ext().thread;
+    let thread = &curr.task_ext().thread;
+    info!("{:?} exit with code: {}", thread, exit_code);
 
-    let clear_child_pid = curr.task_ext().thread_data().clear_child_tid() as *mut i32;
-    if !clear_child_pid.is_null() {
-        // SAFETY: Not safe
-        unsafe { *clear_child_pid = 0 }
-    }
-    let proc = thr.process();
-    if thr.exit(exit_code) {
-        proc.exit();
-    }
+    clear_child_tid();
 
-    if group_exit {
-        proc.group_exit();
-        for thrs in proc.threads() {
-            send_signal_thread(thrs.tid() as core::ffi::c_int, Signal::SIGKILL as u32 as i32); 
+    let process = thread.process();
+    if thread.exit(exit_code) {
+        process.exit();
+        notify_parent_exit(process);
+        // TODO: clear namespace resources
+    }
+    if group_exit && !process.is_group_exited() {
+        process.group_exit();
+        // The exit code is kept, so the other threads dying of `SIGKILL`
+        // do not change what the parent sees.
+        for other in process.threads() {
+            let _ = send_signal_thread(other.tid() as _, Signal::SIGKILL as _);
         }
     }
-    axtask::exit(exit_code);
+    axtask::exit(exit_code)
 }
@@ -0,0 +1,118 @@
+//! Fast user-space locking (`futex`).
+
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use axerrno::{LinuxError, LinuxResult};
+use axsync::spin::SpinNoIrq;
+use axtask::{AxTaskRef, TaskExtRef, current};
+
+use crate::mm::access_user_memory;
+use crate::signal::{discard_ignored_signals, has_unblocked_signal};
+
+/// A futex is identified by the address space and the address of its word,
+/// so threads sharing memory through `CLONE_VM` see the same futex.
+type FutexKey = (usize, usize);
+
+/// A thread blocked in `FUTEX_WAIT`. It sleeps on its own `signal_wq`, so
+/// signals interrupt the wait like any other blocking syscall.
+struct Waiter {
+    task: AxTaskRef,
+    woken: Arc<AtomicBool>,
+}
+
+static FUTEXES: SpinNoIrq<BTreeMap<FutexKey, Vec<Waiter>>> = SpinNoIrq::new(BTreeMap::new());
+
+fn futex_key(uaddr: usize) -> FutexKey {
+    let curr = current();
+    let aspace = Arc::as_ptr(&curr.task_ext().process_data().aspace);
+    (aspace as usize, uaddr)
+}
+
+/// Block until the futex at `uaddr` is woken, if it still holds `val`.
+///
+/// Fails with `EAGAIN` if the word does not hold `val`, with `ETIMEDOUT`
+/// once the relative `timeout` has elapsed, and with `EINTR` if a signal
+/// which is neither blocked nor ignored arrives first.
+///
+/// `uaddr` must have been checked to be a readable user address.
+pub fn futex_wait(uaddr: *const i32, val: i32, timeout: Option<Duration>) -> LinuxResult<isize> {
+    let key = futex_key(uaddr as usize);
+    let woken = Arc::new(AtomicBool::new(false));
+    {
+        // Checking the word under the lock means a `futex_wake` after the
+        // waker changed it can not be missed.
+        let mut futexes = FUTEXES.lock();
+        // SAFETY: The caller has checked the address.
+        if access_user_memory(|| unsafe { uaddr.read_volatile() }) != val {
+            return Err(LinuxError::EAGAIN);
+        }
+        futexes.entry(key).or_default().push(Waiter {
+            task: current().as_task_ref().clone(),
+            woken: woken.clone(),
+        });
+    }
+
+    let curr = current();
+    let signal_wq = &curr.task_ext().thread_data().signal_wq;
+    let deadline = timeout.map(|timeout| axhal::time::monotonic_time() + timeout);
+    let result = loop {
+        let ready = || woken.load(Ordering::Acquire) || has_unblocked_signal();
+        match deadline {
+            None => signal_wq.wait_until(ready),
+            Some(deadline) => {
+                let now = axhal::time::monotonic_time();
+                if now >= deadline {
+                    break Err(LinuxError::ETIMEDOUT);
+                }
+                signal_wq.wait_timeout_until(deadline - now, ready);
+            }
+        }
+        if woken.load(Ordering::Acquire) {
+            return Ok(0);
+        }
+        if has_unblocked_signal() && discard_ignored_signals() {
+            break Err(LinuxError::EINTR);
+        }
+    };
+
+    let mut futexes = FUTEXES.lock();
+    // A wake may have raced with the timeout or the signal.
+    if woken.load(Ordering::Acquire) {
+        return Ok(0);
+    }
+    if let Some(waiters) = futexes.get_mut(&key) {
+        waiters.retain(|waiter| !Arc::ptr_eq(&waiter.woken, &woken));
+        if waiters.is_empty() {
+            futexes.remove(&key);
+        }
+    }
+    result
+}
+
+/// Wake up to `count` threads waiting on the futex at `uaddr`, in the order
+/// they started waiting, and return how many were woken.
+pub fn futex_wake(uaddr: usize, count: usize) -> usize {
+    let key = futex_key(uaddr);
+    let mut futexes = FUTEXES.lock();
+    let Some(waiters) = futexes.get_mut(&key) else {
+        return 0;
+    };
+    let woken = count.min(waiters.len());
+    for waiter in waiters.drain(..woken) {
+        waiter.woken.store(true, Ordering::Release);
+        waiter
+            .task
+            .task_ext()
+            .thread_data()
+            .signal_wq
+            .notify_all(false);
+    }
+    if waiters.is_empty() {
+        futexes.remove(&key);
+    }
+    woken
+}
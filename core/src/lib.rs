@@ -8,8 +8,9 @@
 extern crate axlog;
 extern crate alloc;
 
+pub mod futex;
 pub mod mm;
 pub mod task;
 pub mod signal;
 mod time;
-mod exit;
+pub mod exit;
@@ -10,6 +10,73 @@ use kernel_elf_parser::{AuxvEntry, ELFParser, app_stack_region};
 use memory_addr::{MemoryAddr, PAGE_SIZE_4K, VirtAddr};
 use xmas_elf::{ElfFile, program::SegmentData};
 
+/// Address of the page holding the signal return trampoline, right above the
+/// user stack.
+///
+/// Signal handlers installed without `SA_RESTORER` return here, which then
+/// calls `rt_sigreturn`.
+pub const SIGNAL_TRAMPOLINE: usize = axconfig::plat::USER_STACK_TOP;
+
+/// `mov eax, 15 (rt_sigreturn); syscall`
+#[cfg(target_arch = "x86_64")]
+const SIGNAL_TRAMPOLINE_CODE: &[u8] = &[0xb8, 0x0f, 0x00, 0x00, 0x00, 0x0f, 0x05];
+/// `li a7, 139 (rt_sigreturn); ecall`
+#[cfg(target_arch = "riscv64")]
+const SIGNAL_TRAMPOLINE_CODE: &[u8] = &[0x93, 0x08, 0xb0, 0x08, 0x73, 0x00, 0x00, 0x00];
+/// `mov x8, #139 (rt_sigreturn); svc #0`
+#[cfg(target_arch = "aarch64")]
+const SIGNAL_TRAMPOLINE_CODE: &[u8] = &[0x68, 0x11, 0x80, 0xd2, 0x01, 0x00, 0x00, 0xd4];
+/// `addi.w $a7, $zero, 139 (rt_sigreturn); syscall 0`
+#[cfg(target_arch = "loongarch64")]
+const SIGNAL_TRAMPOLINE_CODE: &[u8] = &[0x0b, 0x2c, 0x82, 0x02, 0x00, 0x00, 0x2b, 0x00];
+
+/// Make the code just written to `[start, start + size)` of `uspace` visible
+/// to instruction fetches on all CPUs.
+///
+/// The code is written through the kernel's linear mapping, and only x86_64
+/// keeps instruction caches coherent with such stores by itself.
+#[cfg_attr(target_arch = "x86_64", allow(unused_variables))]
+fn flush_icache(uspace: &AddrSpace, start: VirtAddr, size: usize) -> AxResult {
+    #[cfg(target_arch = "aarch64")]
+    {
+        use core::arch::asm;
+        use memory_addr::PageIter4K;
+
+        let ctr: usize;
+        // SAFETY: Reading `CTR_EL0` has no side effects.
+        unsafe { asm!("mrs {}, ctr_el0", out(reg) ctr) };
+        // `DminLine` is the log2 of the number of words in a cache line.
+        let line = 4 << ((ctr >> 16) & 0xf);
+        let end = (start + size).align_up_4k();
+        for page in PageIter4K::new(start.align_down_4k(), end).unwrap() {
+            let (paddr, _, _) = uspace
+                .page_table()
+                .query(page)
+                .map_err(|_| AxError::BadAddress)?;
+            let vaddr = axhal::mem::phys_to_virt(paddr).as_usize();
+            for addr in (vaddr..vaddr + PAGE_SIZE_4K).step_by(line) {
+                // SAFETY: Cleaning the data cache does not change memory.
+                unsafe { asm!("dc cvau, {}", in(reg) addr) };
+            }
+        }
+        // SAFETY: Invalidating the instruction caches does not change memory.
+        unsafe { asm!("dsb ish", "ic ialluis", "dsb ish", "isb") };
+    }
+    #[cfg(target_arch = "riscv64")]
+    {
+        // A `hart_mask_base` of -1 selects all harts.
+        let _ = sbi_rt::remote_fence_i(sbi_rt::HartMask::from_mask_base(0, usize::MAX));
+    }
+    #[cfg(target_arch = "loongarch64")]
+    {
+        // The instruction caches are coherent with stores, but the pipeline
+        // may still hold stale instructions.
+        // SAFETY: `ibar` only orders instruction fetches.
+        unsafe { core::arch::asm!("ibar 0") };
+    }
+    Ok(())
+}
+
 /// Creates a new empty user address space.
 pub fn new_user_aspace_empty() -> AxResult<AddrSpace> {
     AddrSpace::new_empty(
@@ -71,7 +138,9 @@ fn map_elf(uspace: &mut AddrSpace, elf: &ElfFile) -> AxResult<(VirtAddr, [AuxvEn
             .get(segement.offset..segement.offset + segement.filesz as usize)
             .ok_or(AxError::InvalidData)?;
         uspace.write(segement.vaddr, seg_data)?;
-        // TODO: flush the I-cache
+        if segement.flags.contains(MappingFlags::EXECUTE) {
+            flush_icache(uspace, segement.vaddr, seg_data.len())?;
+        }
     }
 
     Ok((
@@ -162,6 +231,16 @@ pub fn load_user_app(
         true,
     )?;
 
+    let trampoline = VirtAddr::from_usize(SIGNAL_TRAMPOLINE);
+    uspace.map_alloc(
+        trampoline,
+        PAGE_SIZE_4K,
+        MappingFlags::READ | MappingFlags::EXECUTE | MappingFlags::USER,
+        true,
+    )?;
+    uspace.write(trampoline, SIGNAL_TRAMPOLINE_CODE)?;
+    flush_icache(uspace, trampoline, SIGNAL_TRAMPOLINE_CODE.len())?;
+
     let user_sp = ustack_end - stack_data.len();
 
     uspace.write(user_sp, stack_data.as_slice())?;
@@ -1,7 +1,10 @@
 use super::exit::do_exit;
+use super::mm::{SIGNAL_TRAMPOLINE, access_user_memory};
 use super::task::{ProcessData, ThreadData};
+use alloc::collections::VecDeque;
 use axerrno::{LinuxError, LinuxResult};
 use axhal::arch::TrapFrame;
+use axhal::paging::MappingFlags;
 use axhal::trap::{POST_TRAP, register_trap_handler};
 use axtask::{TaskExtRef, current};
 use core::{
@@ -9,7 +12,13 @@ use core::{
     ffi::c_int,
     fmt::Result,
     marker::Sized,
+    time::Duration,
 };
+use memory_addr::{MemoryAddr, VirtAddr, VirtAddrRange};
+
+mod ucontext;
+
+use ucontext::UContext;
 
 #[macro_export]
 macro_rules! define_signals {
@@ -35,13 +44,28 @@ macro_rules! define_signals {
                     _ => None,
                 }
             }
+
+            /// The name of the variant, e.g. `"SIGTERM"`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$FIELD => stringify!($FIELD),)*
+                }
+            }
+
+            /// The variant called `name`, e.g. `"SIGTERM"`.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($FIELD) => Some(Self::$FIELD),)*
+                    _ => None,
+                }
+            }
         }
 
         bitflags::bitflags! {
             $(#[$enum_meta])*
-            pub struct SigMask: u32 {
+            pub struct SigMask: u64 {
                 $(
-                    const $FIELD = 1 << $value;
+                    const $FIELD = 1 << ($value - 1);
                 )*
             }
         }
@@ -132,14 +156,144 @@ define_signals! {
     }
 }
 
+impl Signal {
+    /// Whether `signo` is a real-time signal, i.e. in `SIGRTMIN..=SIGRTMAX`.
+    pub fn is_real_time(signo: u32) -> bool {
+        (SIGRTMIN as u32..=SIGRTMAX as u32).contains(&signo)
+    }
+
+    /// The action taken on the signal while its disposition is
+    /// [`SigDisposition::Default`].
+    pub fn default_disposition(&self) -> SigDisposition {
+        DEFAULT_ACTIONS[*self as usize]
+    }
+}
+
+impl Default for SigMask {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl SigMask {
+    /// The mask containing only `sig`.
+    pub fn from_signal(sig: Signal) -> Self {
+        Self::from_bits_retain(1 << (sig as u32 - 1))
+    }
+
+    /// The mask containing only signal number `signo`, which may be a
+    /// real-time signal, or `None` if `signo` is not in `1..=_NSIG`.
+    pub fn from_signo(signo: u32) -> Option<Self> {
+        (1..=_NSIG as u32)
+            .contains(&signo)
+            .then(|| Self::from_bits_retain(1 << (signo - 1)))
+    }
+
+    /// Convert from a Linux `sigset_t`, in which signal `n` is bit `n - 1`.
+    pub fn from_sigset(set: u64) -> Self {
+        Self::from_bits_retain(set)
+    }
+
+    /// Convert to a Linux `sigset_t`, in which signal `n` is bit `n - 1`.
+    pub fn to_sigset(self) -> u64 {
+        self.bits()
+    }
+}
+
+/// Signals which can never be blocked, ignored or caught.
+pub const UNBLOCKABLE: SigMask = SigMask::SIGKILL.union(SigMask::SIGSTOP);
+
 /// Count of signals
-const _NSIG: i32 = 32;
+pub const _NSIG: i32 = 64;
 /// Real-time signals (platform-specific)
 pub const SIGRTMIN: i32 = 32;
 /// Maximum real-time signal (platform-specific)
-pub const SIGRTMAX: i32 = _NSIG - 1;
+pub const SIGRTMAX: i32 = _NSIG;
+/// Set in the wait status of a process killed by a signal that dumped core;
+/// the signal number is in the low 7 bits
+pub const WCOREFLAG: i32 = 0x80;
 
-#[derive(Default)]
+/// `si_code` of a signal sent by `kill`
+pub const SI_USER: i32 = 0;
+/// `si_code` of a signal sent by the kernel
+pub const SI_KERNEL: i32 = 0x80;
+/// `si_code` of a signal sent by `sigqueue`
+pub const SI_QUEUE: i32 = -1;
+/// `si_code` of a signal sent by `tkill` or `tgkill`
+pub const SI_TKILL: i32 = -6;
+
+/// A pending signal along with the information `rt_sigtimedwait` reports
+/// about it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SigInfo {
+    /// Signal number
+    pub signo: u32,
+    /// Origin of the signal, one of the `SI_*` codes
+    pub code: i32,
+    /// Process ID of the sender
+    pub pid: u32,
+    /// Real user ID of the sender
+    pub uid: u32,
+    /// Payload attached by `sigqueue`
+    pub value: usize,
+}
+
+impl SigInfo {
+    /// Information about signal `signo` sent by the kernel.
+    pub fn kernel(signo: u32) -> Self {
+        Self {
+            signo,
+            code: SI_KERNEL,
+            ..Default::default()
+        }
+    }
+}
+
+/// `siginfo_t` as used by `rt_sigqueueinfo`, `rt_sigtimedwait` and
+/// `SA_SIGINFO` handlers, with the fields of signals sent by a process.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct QueuedSigInfo {
+    signo: i32,
+    errno: i32,
+    code: i32,
+    _pad: i32,
+    pid: u32,
+    uid: u32,
+    value: usize,
+    _rest: [u8; 96],
+}
+
+const _: () = assert!(size_of::<QueuedSigInfo>() == 128);
+
+impl From<SigInfo> for QueuedSigInfo {
+    fn from(info: SigInfo) -> Self {
+        Self {
+            signo: info.signo as _,
+            errno: 0,
+            code: info.code,
+            _pad: 0,
+            pid: info.pid,
+            uid: info.uid,
+            value: info.value,
+            _rest: [0; 96],
+        }
+    }
+}
+
+impl From<QueuedSigInfo> for SigInfo {
+    fn from(info: QueuedSigInfo) -> Self {
+        Self {
+            signo: info.signo as _,
+            code: info.code,
+            pid: info.pid,
+            uid: info.uid,
+            value: info.value,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
 pub enum SigDisposition {
     #[default]
     Default,
@@ -148,12 +302,34 @@ pub enum SigDisposition {
     Terminate,
     Stop,
     Continue,
+    /// Run the user handler at the given address
+    Handler(usize),
 }
 
-#[derive(Default)]
+bitflags::bitflags! {
+    /// Flags of `struct sigaction`
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct SigActionFlags: u32 {
+        const SA_NOCLDSTOP = 0x0000_0001;
+        const SA_NOCLDWAIT = 0x0000_0002;
+        const SA_SIGINFO = 0x0000_0004;
+        const SA_RESTORER = 0x0400_0000;
+        const SA_ONSTACK = 0x0800_0000;
+        const SA_RESTART = 0x1000_0000;
+        const SA_NODEFER = 0x4000_0000;
+        const SA_RESETHAND = 0x8000_0000;
+    }
+}
+
+#[derive(Default, Clone, Copy)]
 pub struct SignalAction {
-    // TODO
     pub disposition: SigDisposition,
+    /// Signals blocked while the handler runs, in addition to the delivered
+    /// one unless `SA_NODEFER` is set
+    pub sa_mask: SigMask,
+    pub sa_flags: SigActionFlags,
+    /// Where the handler returns to when `SA_RESTORER` is set
+    pub sa_restorer: usize,
 }
 
 #[derive(Clone, Copy)]
@@ -236,52 +412,94 @@ const DEFAULT_ACTIONS: [SigDisposition; 32] = [
 /// Find qualified thread belonging to the proc to recv sig
 /// Add sig to `pending`
 pub fn send_signal_proc(pid: c_int, sig: c_int) -> LinuxResult<isize> {
+    send_siginfo_proc(pid, SigInfo::kernel(sig as u32))
+}
+
+/// Like [`send_signal_proc`], but queues `info` instead of a bare signal
+/// number. A signal number of 0 only checks that the process exists.
+pub fn send_siginfo_proc(pid: c_int, info: SigInfo) -> LinuxResult<isize> {
     let cur_proc = super::task::PROCESS_TABLE
         .read()
         .get(&(pid as u32))
         .ok_or(LinuxError::ESRCH)?;
 
-    if sig == 0 {
+    if info.signo == 0 {
         return Ok(0);
     }
 
-    let signal_index = SigMask::from_bits(1 << sig).ok_or(LinuxError::EINVAL)?;
-    let signal = Signal::from_u32(sig).ok_or(LinuxError::EINVAL)?;
+    let signal_index = SigMask::from_signo(info.signo).ok_or(LinuxError::EINVAL)?;
 
     for thread in cur_proc.threads().iter() {
         let thread_data: &ThreadData = thread.data().unwrap();
-        if !thread_data.blocked.contains(signal_index) {
+        if !(*thread_data.blocked.lock() - UNBLOCKABLE).contains(signal_index) {
             // Checked by SigMask
-            thread_data.pending.lock().push_back(signal);
+            thread_data.pending.lock().push_back(info);
+            thread_data.signal_wq.notify_one(false);
             return Ok(0);
         }
     }
     let proc_data: &ProcessData = cur_proc.data().unwrap();
-    proc_data.shared.lock().push_back(signal);
+    proc_data.shared.lock().push_back(info);
+    for thread in cur_proc.threads().iter() {
+        let thread_data: &ThreadData = thread.data().unwrap();
+        thread_data.signal_wq.notify_one(false);
+    }
     Ok(0)
 }
 
 pub fn send_signal_thread(tid: c_int, sig: c_int) -> LinuxResult<isize> {
+    send_siginfo_thread(tid, SigInfo::kernel(sig as u32))
+}
+
+/// Like [`send_signal_thread`], but queues `info` instead of a bare signal
+/// number. A signal number of 0 only checks that the thread exists.
+pub fn send_siginfo_thread(tid: c_int, info: SigInfo) -> LinuxResult<isize> {
     let thread = super::task::THREAD_TABLE
         .read()
         .get(&(tid as u32))
         .ok_or(LinuxError::ESRCH)?;
     let thread_data: &ThreadData = thread.data().unwrap();
 
-    let signal_index = SigMask::from_bits(1 << sig).ok_or(LinuxError::EINVAL)?;
-    let signal = Signal::from_u32(sig).ok_or(LinuxError::EINVAL)?;
+    if info.signo == 0 {
+        return Ok(0);
+    }
 
-    if !thread_data.blocked.contains(signal_index) {
-        thread_data.pending.lock().push_back(signal);
-        Ok(0)
-    } else {
-        Err(LinuxError::EINVAL)
+    SigMask::from_signo(info.signo).ok_or(LinuxError::EINVAL)?;
+
+    // A blocked signal stays pending until the thread unblocks it
+    thread_data.pending.lock().push_back(info);
+    thread_data.signal_wq.notify_one(false);
+    Ok(0)
+}
+
+/// Like [`send_siginfo_thread`], but fails with `ESRCH` unless the thread
+/// belongs to the process `tgid`.
+pub fn send_siginfo_thread_in_group(tgid: c_int, tid: c_int, info: SigInfo) -> LinuxResult<isize> {
+    if tgid <= 0 || tid <= 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let thread = super::task::THREAD_TABLE
+        .read()
+        .get(&(tid as u32))
+        .ok_or(LinuxError::ESRCH)?;
+    if thread.process().pid() != tgid as u32 {
+        return Err(LinuxError::ESRCH);
     }
+    send_siginfo_thread(tid, info)
 }
 
 pub fn handle_signal(on_action: &SignalAction, signo: u32) -> Option<SignalOSAction> {
-    match on_action.disposition {
-        SigDisposition::Default => match DEFAULT_ACTIONS[signo as usize] {
+    let mask = SigMask::from_signo(signo).unwrap_or_default();
+    let disposition = if UNBLOCKABLE.intersects(mask) {
+        SigDisposition::Default
+    } else {
+        on_action.disposition
+    };
+    match disposition {
+        // Real-time signals terminate the process by default
+        SigDisposition::Default => match Signal::from_u32(signo as _)
+            .map_or(SigDisposition::Terminate, |sig| sig.default_disposition())
+        {
             SigDisposition::Ignore => None,
             SigDisposition::Default => panic!("Invalid default disposition"),
             SigDisposition::Stop => Some(SignalOSAction::Stop),
@@ -294,8 +512,357 @@ pub fn handle_signal(on_action: &SignalAction, signo: u32) -> Option<SignalOSAct
         SigDisposition::Stop => Some(SignalOSAction::Stop),
         SigDisposition::Continue => Some(SignalOSAction::Continue),
         SigDisposition::Ignore => None,
+        SigDisposition::Handler(_) => {
+            let mut add_blocked = on_action.sa_mask;
+            if !on_action.sa_flags.contains(SigActionFlags::SA_NODEFER) {
+                add_blocked |= mask;
+            }
+            Some(SignalOSAction::Handler { add_blocked })
+        }
+    }
+}
+
+/// Frame pushed on the user stack while a handler runs, holding the
+/// `siginfo_t` and the `ucontext_t` the handler gets, from which
+/// `rt_sigreturn` restores the interrupted context.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SignalFrame {
+    info: QueuedSigInfo,
+    uc: UContext,
+}
+
+pub(crate) fn access_user(addr: usize, len: usize, flags: MappingFlags) -> LinuxResult<()> {
+    let curr = current();
+    let mut aspace = curr.task_ext().process_data().aspace.lock();
+    let start = VirtAddr::from_usize(addr);
+    if !aspace.check_region_access(VirtAddrRange::from_start_size(start, len), flags) {
+        return Err(LinuxError::EFAULT);
+    }
+    let page_start = start.align_down_4k();
+    let page_end = (start + len).align_up_4k();
+    aspace.populate_area(page_start, page_end - page_start)?;
+    Ok(())
+}
+
+fn write_frame(addr: usize, frame: &SignalFrame) -> LinuxResult<()> {
+    access_user(
+        addr,
+        size_of::<SignalFrame>(),
+        MappingFlags::READ | MappingFlags::WRITE,
+    )?;
+    // SAFETY: The region has been checked and populated.
+    access_user_memory(|| unsafe { (addr as *mut SignalFrame).write_unaligned(*frame) });
+    Ok(())
+}
+
+fn read_frame(addr: usize) -> LinuxResult<SignalFrame> {
+    access_user(addr, size_of::<SignalFrame>(), MappingFlags::READ)?;
+    // SAFETY: The region has been checked and populated.
+    Ok(access_user_memory(|| unsafe {
+        (addr as *const SignalFrame).read_unaligned()
+    }))
+}
+
+/// Redirect `tf` to the handler of `action` for the signal described by
+/// `info`, saving the current context and `blocked` in a frame on the user
+/// stack.
+///
+/// The handler gets pointers to the `siginfo_t` and the `ucontext_t` in the
+/// frame as its second and third arguments, which `SA_SIGINFO` handlers use.
+fn setup_signal_frame(
+    tf: &mut TrapFrame,
+    info: SigInfo,
+    action: &SignalAction,
+    blocked: SigMask,
+) -> LinuxResult<()> {
+    let SigDisposition::Handler(handler) = action.disposition else {
+        unreachable!("signal {} has no handler", info.signo);
+    };
+    let restorer = if action.sa_flags.contains(SigActionFlags::SA_RESTORER) {
+        action.sa_restorer
+    } else {
+        SIGNAL_TRAMPOLINE
+    };
+
+    let mut sp = tf.sp();
+    // Skip the red zone below the stack pointer.
+    #[cfg(target_arch = "x86_64")]
+    {
+        sp -= 128;
+    }
+    let frame_addr = (sp - size_of::<SignalFrame>()) & !0xf;
+    let uc_addr = frame_addr + core::mem::offset_of!(SignalFrame, uc);
+    let mut frame = SignalFrame {
+        info: info.into(),
+        uc: UContext::new(tf, blocked),
+    };
+    frame.uc.set_user_addr(uc_addr);
+    write_frame(frame_addr, &frame)?;
+    sp = frame_addr;
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        // The handler returns by popping the restorer off the stack.
+        sp -= size_of::<usize>();
+        access_user(
+            sp,
+            size_of::<usize>(),
+            MappingFlags::READ | MappingFlags::WRITE,
+        )?;
+        // SAFETY: The region has been checked and populated.
+        access_user_memory(|| unsafe { (sp as *mut usize).write(restorer) });
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    tf.set_ra(restorer);
+
+    tf.set_sp(sp);
+    tf.set_ip(handler);
+    tf.set_arg0(info.signo as usize);
+    tf.set_arg1(frame_addr);
+    tf.set_arg2(uc_addr);
+    Ok(())
+}
+
+/// Install `saved` into `tf`, keeping the privileged state of `tf` so that
+/// user space can not raise its privilege through a forged frame.
+fn restore_user_context(tf: &mut TrapFrame, mut saved: TrapFrame) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // CF, PF, AF, ZF, SF, TF, DF, OF and AC
+        const USER_RFLAGS: u64 = 0x40dd5;
+        saved.cs = tf.cs;
+        saved.ss = tf.ss;
+        saved.rflags = (saved.rflags & USER_RFLAGS) | (tf.rflags & !USER_RFLAGS);
+    }
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    {
+        saved.sstatus = tf.sstatus;
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        const NZCV: u64 = 0xf000_0000;
+        saved.spsr = (saved.spsr & NZCV) | (tf.spsr & !NZCV);
+    }
+    #[cfg(target_arch = "loongarch64")]
+    {
+        saved.prmd = tf.prmd;
+    }
+    *tf = saved;
+}
+
+/// Return from a signal handler: restore the context and the blocked signals
+/// saved by [`check_signals`] on the user stack, which `tf.sp()` points to,
+/// including any changes the handler made to the `ucontext_t`.
+///
+/// The thread is killed with `SIGSEGV` if the frame can not be read.
+pub fn restore_signal_frame(tf: &mut TrapFrame) {
+    let Ok(frame) = read_frame(tf.sp()) else {
+        do_exit(Signal::SIGSEGV as i32 | WCOREFLAG, true);
+    };
+    let mut saved = *tf;
+    frame.uc.restore(&mut saved);
+    restore_user_context(tf, saved);
+    *current().task_ext().thread_data().blocked.lock() = frame.uc.sigmask() - UNBLOCKABLE;
+}
+
+/// Length of the instruction that traps into a syscall.
+#[cfg(target_arch = "x86_64")]
+const SYSCALL_INSN_LEN: usize = 2;
+#[cfg(not(target_arch = "x86_64"))]
+const SYSCALL_INSN_LEN: usize = 4;
+
+/// Record that the syscall entered with `tf` failed with `EINTR`, so that
+/// [`check_signals`] can restart it.
+///
+/// The syscall is restarted if the signal has no handler, or if the handler
+/// was installed with `SA_RESTART`.
+pub fn mark_syscall_interrupted(tf: &TrapFrame) {
+    let mut restart = *tf;
+    restart.set_ip(tf.ip() - SYSCALL_INSN_LEN);
+    *current().task_ext().thread_data().interrupted.lock() = Some(restart);
+}
+
+/// Take the first pending signal of the current thread for which `wanted`
+/// holds, looking at the thread's own queue before the process-wide one.
+fn take_pending(
+    thread_data: &ThreadData,
+    proc_data: &ProcessData,
+    wanted: impl Fn(u32) -> bool,
+) -> Option<SigInfo> {
+    let take = |queue: &mut VecDeque<SigInfo>| {
+        let index = queue.iter().position(|info| wanted(info.signo))?;
+        queue.remove(index)
+    };
+    take(&mut thread_data.pending.lock()).or_else(|| take(&mut proc_data.shared.lock()))
+}
+
+/// Take the first signal in `queue` that is not in `blocked` and whose action
+/// is not to ignore it, dropping the ignored ones in front of it.
+///
+/// Returns it and what to do about it. Only looks at its arguments, so it
+/// does not depend on the current task.
+fn dequeue_signal(
+    queue: &mut VecDeque<SigInfo>,
+    blocked: SigMask,
+    actions: &[SignalAction],
+) -> Option<(SigInfo, SignalOSAction)> {
+    let blocked = blocked - UNBLOCKABLE;
+    let unblocked = |info: &SigInfo| !blocked.intersects(SigMask::from_signo(info.signo).unwrap());
+    while let Some(index) = queue.iter().position(unblocked) {
+        let info = queue.remove(index).unwrap();
+        if let Some(on_action) = handle_signal(&actions[info.signo as usize], info.signo) {
+            return Some((info, on_action));
+        }
+    }
+    None
+}
+
+/// Whether the current thread has a pending signal for which `wanted` holds.
+fn has_pending(wanted: impl Fn(u32) -> bool) -> bool {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    let proc_data = curr.task_ext().process_data();
+    let wanted = |info: &SigInfo| wanted(info.signo);
+    thread_data.pending.lock().iter().any(wanted) || proc_data.shared.lock().iter().any(wanted)
+}
+
+/// The signals pending for the current thread, blocked or not.
+pub fn pending_signals() -> SigMask {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    let proc_data = curr.task_ext().process_data();
+    let pending = thread_data.pending.lock();
+    let shared = proc_data.shared.lock();
+    pending
+        .iter()
+        .chain(shared.iter())
+        .fold(SigMask::empty(), |mask, info| {
+            mask | SigMask::from_signo(info.signo).unwrap()
+        })
+}
+
+/// Whether the current thread has a pending signal that is not blocked.
+pub(crate) fn has_unblocked_signal() -> bool {
+    let blocked = *current().task_ext().thread_data().blocked.lock() - UNBLOCKABLE;
+    has_pending(|signo| !blocked.intersects(SigMask::from_signo(signo).unwrap()))
+}
+
+/// Drop the unblocked pending signals of the current thread whose action is
+/// to ignore them, and report whether any unblocked signal is left.
+pub(crate) fn discard_ignored_signals() -> bool {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    let proc_data = curr.task_ext().process_data();
+    let actions = proc_data.actions.lock();
+    let blocked = *thread_data.blocked.lock() - UNBLOCKABLE;
+
+    let mut deliverable = false;
+    let mut keep = |&SigInfo { signo, .. }: &SigInfo| {
+        if blocked.intersects(SigMask::from_signo(signo).unwrap()) {
+            return true;
+        }
+        let keep = handle_signal(&actions[signo as usize], signo).is_some();
+        deliverable |= keep;
+        keep
+    };
+    thread_data.pending.lock().retain(&mut keep);
+    proc_data.shared.lock().retain(&mut keep);
+    deliverable
+}
+
+/// Block the current thread until it has a pending signal which is neither
+/// blocked nor ignored. Ignored signals arriving meanwhile are discarded.
+pub fn wait_for_signal() {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    loop {
+        thread_data.signal_wq.wait_until(has_unblocked_signal);
+        if discard_ignored_signals() {
+            return;
+        }
+    }
+}
+
+/// Block the current thread until `condition` holds, or until it has a
+/// pending signal which is neither blocked nor ignored, in which case `EINTR`
+/// is returned.
+///
+/// Whoever makes `condition` hold must notify the thread's `signal_wq`.
+pub fn wait_interruptible(condition: impl Fn() -> bool) -> LinuxResult<()> {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    loop {
+        thread_data
+            .signal_wq
+            .wait_until(|| condition() || has_unblocked_signal());
+        if condition() {
+            return Ok(());
+        }
+        if discard_ignored_signals() {
+            return Err(LinuxError::EINTR);
+        }
+    }
+}
+
+/// Block the current thread for `dur`, or until it has a pending signal which
+/// is neither blocked nor ignored.
+///
+/// Returns the time left if the sleep was cut short by a signal.
+pub fn sleep_interruptible(dur: Duration) -> Option<Duration> {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    let deadline = axhal::time::wall_time() + dur;
+    loop {
+        let now = axhal::time::wall_time();
+        if now >= deadline {
+            return None;
+        }
+        let timeout = thread_data
+            .signal_wq
+            .wait_timeout_until(deadline - now, has_unblocked_signal);
+        if !timeout && discard_ignored_signals() {
+            return Some(deadline.saturating_sub(axhal::time::wall_time()));
+        }
+    }
+}
+
+/// Wait for a signal in `set` to become pending and take it, as
+/// `rt_sigtimedwait` does.
+///
+/// Fails with `EAGAIN` once `timeout` has elapsed, and with `EINTR` if a
+/// signal outside `set` which is neither blocked nor ignored arrives first.
+/// `SIGKILL` and `SIGSTOP` can not be waited for.
+pub fn wait_for_signal_in(set: SigMask, timeout: Option<Duration>) -> LinuxResult<SigInfo> {
+    let curr = current();
+    let thread_data = curr.task_ext().thread_data();
+    let proc_data = curr.task_ext().process_data();
+    let set = set - UNBLOCKABLE;
+    let in_set = |signo: u32| set.intersects(SigMask::from_signo(signo).unwrap());
+    let deadline = timeout.map(|timeout| axhal::time::wall_time() + timeout);
+    loop {
+        if let Some(info) = take_pending(thread_data, proc_data, in_set) {
+            return Ok(info);
+        }
+        if discard_ignored_signals() {
+            return Err(LinuxError::EINTR);
+        }
+        let arrived = || has_pending(in_set) || has_unblocked_signal();
+        match deadline {
+            None => thread_data.signal_wq.wait_until(arrived),
+            Some(deadline) => {
+                let now = axhal::time::wall_time();
+                if now >= deadline {
+                    return Err(LinuxError::EAGAIN);
+                }
+                thread_data
+                    .signal_wq
+                    .wait_timeout_until(deadline - now, arrived);
+            }
+        }
     }
 }
+
 /*
 pending 存放信号, 由 send_signal 发送, 顺便快速检查有无能解锁的任务
 
@@ -307,33 +874,43 @@ pending 存放信号, 由 send_signal 发送, 顺便快速检查有无能解锁
 [1]: 信号行为数组有 Default 值, 所以在没有 rt_sigaction[2] 的情况下模式匹配会到 Disposition::Default 处理, 提供了 DEFUALT_ACTIONS
 [2]: 获取信号操作或修改信号操作, 实际上是获取或更改 ProcessData 的 signal_actions
 */
+/// Deliver the first deliverable pending signal of the current thread.
+///
+/// Returns whether a signal was delivered. Does not return if the signal
+/// terminates the process.
 pub fn check_signals(tf: &mut TrapFrame) -> bool {
     info!("Handle signals.");
     let current = axtask::current();
     let data = current.task_ext().thread_data();
-    let actions = current.task_ext().process_data().actions.lock();
+    let proc_data = current.task_ext().process_data();
+    let mut actions = proc_data.actions.lock();
 
-    let mask = !data.blocked;
-    let (signo, on_action) = loop {
-        let pending = data.pending.lock();
-        let Some(sig) = pending.front() else {
-            return false;
-        };
-        let signo = *sig as u32;
-        if mask.contains(SigMask::from_bits(1 << signo).expect("Wrong signo")) {
-            continue;
-        }
-        if let Some(on_action) = handle_signal(&actions[signo as usize], signo) {
-            break (signo, on_action);
+    let blocked = *data.blocked.lock();
+    // The thread's own queue comes before the process-wide one.
+    let delivered = dequeue_signal(&mut data.pending.lock(), blocked, &*actions)
+        .or_else(|| dequeue_signal(&mut proc_data.shared.lock(), blocked, &*actions))
+        .map(|(info, on_action)| (info, actions[info.signo as usize], on_action));
+    if let Some((info, _, SignalOSAction::Handler { .. })) = delivered {
+        handler_started(&mut actions[info.signo as usize]);
+    }
+    drop(actions);
+    // The mask installed by `rt_sigsuspend` only lasts until a signal is
+    // delivered.
+    let saved = data.saved.lock().take();
+    let interrupted = data.interrupted.lock().take();
+    let Some((info, action, on_action)) = delivered else {
+        if let Some(saved) = saved {
+            *data.blocked.lock() = saved;
         }
+        *tf = resume_context(tf, interrupted, None);
+        return false;
     };
-    drop(actions);
     match on_action {
         SignalOSAction::CoreDump => {
-            do_exit(128 + signo as i32, true);
+            do_exit(info.signo as i32 | WCOREFLAG, true);
         }
         SignalOSAction::Terminate => {
-            do_exit(128 + signo as i32, true);
+            do_exit(info.signo as i32, true);
         }
         SignalOSAction::Stop => {
             // TODO
@@ -343,10 +920,61 @@ pub fn check_signals(tf: &mut TrapFrame) -> bool {
             // TODO: continue
         }
         SignalOSAction::Handler { add_blocked } => {
-            // TODO: add blocked
+            *tf = resume_context(tf, interrupted, Some(&action));
+            let (frame_mask, handler_mask) = handler_masks(blocked, saved, add_blocked);
+            if setup_signal_frame(tf, info, &action, frame_mask).is_err() {
+                do_exit(Signal::SIGSEGV as i32 | WCOREFLAG, true);
+            }
+            *data.blocked.lock() = handler_mask;
+            return true;
         }
     }
-    unimplemented!("😅: check_signals");
+    if let Some(saved) = saved {
+        *data.blocked.lock() = saved;
+    }
+    *tf = resume_context(tf, interrupted, None);
+    true
+}
+
+/// Note that the handler of `action` is about to run: a handler installed
+/// with `SA_RESETHAND` only runs once.
+fn handler_started(action: &mut SignalAction) {
+    if action.sa_flags.contains(SigActionFlags::SA_RESETHAND) {
+        action.disposition = SigDisposition::Default;
+    }
+}
+
+/// The context to resume after a signal was taken in `tf`.
+///
+/// A syscall interrupted with `EINTR` is restarted from `interrupted`, unless
+/// `handler` runs first without `SA_RESTART`, in which case it returns to the
+/// `EINTR`.
+fn resume_context(
+    tf: &TrapFrame,
+    interrupted: Option<TrapFrame>,
+    handler: Option<&SignalAction>,
+) -> TrapFrame {
+    let restart = handler.is_none_or(|action| action.sa_flags.contains(SigActionFlags::SA_RESTART));
+    match interrupted {
+        Some(interrupted) if restart => interrupted,
+        _ => *tf,
+    }
+}
+
+/// The mask saved in a handler's frame for `rt_sigreturn`, and the one the
+/// handler runs with.
+///
+/// If `rt_sigsuspend` installed `blocked`, the mask it `saved` is the one to
+/// get back to once the handler returns.
+fn handler_masks(
+    blocked: SigMask,
+    saved: Option<SigMask>,
+    add_blocked: SigMask,
+) -> (SigMask, SigMask) {
+    (
+        saved.unwrap_or(blocked),
+        (blocked | add_blocked) - UNBLOCKABLE,
+    )
 }
 
 #[register_trap_handler(POST_TRAP)]
@@ -356,3 +984,202 @@ fn post_trap_callback(tf: &mut TrapFrame, from_user: bool) {
     }
     check_signals(tf);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGUSR1: u32 = Signal::SIGUSR1 as u32;
+    const SIGUSR2: u32 = Signal::SIGUSR2 as u32;
+    const SIGTERM: u32 = Signal::SIGTERM as u32;
+    const SIGKILL: u32 = Signal::SIGKILL as u32;
+
+    fn actions() -> [SignalAction; _NSIG as usize + 1] {
+        [SignalAction::default(); _NSIG as usize + 1]
+    }
+
+    fn handler(sa_flags: SigActionFlags) -> SignalAction {
+        SignalAction {
+            disposition: SigDisposition::Handler(0x1000),
+            sa_flags,
+            ..Default::default()
+        }
+    }
+
+    fn queue(signals: &[u32]) -> VecDeque<SigInfo> {
+        signals
+            .iter()
+            .map(|&signo| SigInfo::kernel(signo))
+            .collect()
+    }
+
+    fn mask(signo: u32) -> SigMask {
+        SigMask::from_signo(signo).unwrap()
+    }
+
+    fn trap_frame(ip: usize) -> TrapFrame {
+        let mut tf = TrapFrame::default();
+        tf.set_ip(ip);
+        tf
+    }
+
+    #[test]
+    fn blocked_signal_stays_pending() {
+        let mut actions = actions();
+        actions[SIGUSR1 as usize] = handler(SigActionFlags::empty());
+        let mut pending = queue(&[SIGUSR1]);
+
+        assert!(dequeue_signal(&mut pending, mask(SIGUSR1), &actions).is_none());
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[test]
+    fn unblocked_signal_is_delivered() {
+        let mut actions = actions();
+        actions[SIGUSR1 as usize] = handler(SigActionFlags::empty());
+        let mut pending = queue(&[SIGUSR1]);
+
+        let (info, on_action) = dequeue_signal(&mut pending, SigMask::empty(), &actions).unwrap();
+        assert_eq!(info.signo, SIGUSR1);
+        assert!(matches!(on_action, SignalOSAction::Handler { .. }));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn ignored_signal_is_dropped() {
+        let mut actions = actions();
+        actions[SIGUSR2 as usize].disposition = SigDisposition::Ignore;
+        actions[SIGUSR1 as usize] = handler(SigActionFlags::empty());
+        let mut pending = queue(&[SIGUSR2, SIGUSR1]);
+
+        let (info, _) = dequeue_signal(&mut pending, SigMask::empty(), &actions).unwrap();
+        assert_eq!(info.signo, SIGUSR1);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn default_action_terminates() {
+        let mut pending = queue(&[SIGTERM]);
+
+        let (_, on_action) = dequeue_signal(&mut pending, SigMask::empty(), &actions()).unwrap();
+        assert!(matches!(on_action, SignalOSAction::Terminate));
+    }
+
+    #[test]
+    fn sigkill_can_not_be_blocked_or_caught() {
+        let mut actions = actions();
+        actions[SIGKILL as usize] = handler(SigActionFlags::empty());
+        let mut pending = queue(&[SIGKILL]);
+
+        let (_, on_action) = dequeue_signal(&mut pending, SigMask::all(), &actions).unwrap();
+        assert!(matches!(on_action, SignalOSAction::Terminate));
+    }
+
+    #[test]
+    fn handler_blocks_its_signal_unless_nodefer() {
+        let Some(SignalOSAction::Handler { add_blocked }) =
+            handle_signal(&handler(SigActionFlags::empty()), SIGUSR1)
+        else {
+            panic!("SIGUSR1 has a handler");
+        };
+        assert!(add_blocked.contains(mask(SIGUSR1)));
+
+        let Some(SignalOSAction::Handler { add_blocked }) =
+            handle_signal(&handler(SigActionFlags::SA_NODEFER), SIGUSR1)
+        else {
+            panic!("SIGUSR1 has a handler");
+        };
+        assert!(!add_blocked.contains(mask(SIGUSR1)));
+    }
+
+    #[test]
+    fn handler_masks_save_and_restore() {
+        let (frame_mask, handler_mask) = handler_masks(mask(SIGUSR2), None, mask(SIGUSR1));
+        assert_eq!(frame_mask, mask(SIGUSR2));
+        assert_eq!(handler_mask, mask(SIGUSR1) | mask(SIGUSR2));
+
+        // The mask from before `rt_sigsuspend` is the one to restore.
+        let (frame_mask, _) = handler_masks(mask(SIGUSR2), Some(SigMask::empty()), mask(SIGUSR1));
+        assert_eq!(frame_mask, SigMask::empty());
+
+        let (_, handler_mask) = handler_masks(SigMask::empty(), None, UNBLOCKABLE);
+        assert!(handler_mask.is_empty());
+    }
+
+    #[test]
+    fn resethand_resets_to_default() {
+        let mut action = handler(SigActionFlags::SA_RESETHAND);
+        handler_started(&mut action);
+        assert!(matches!(action.disposition, SigDisposition::Default));
+
+        let mut action = handler(SigActionFlags::empty());
+        handler_started(&mut action);
+        assert!(matches!(action.disposition, SigDisposition::Handler(_)));
+    }
+
+    #[test]
+    fn interrupted_syscall_restarts_with_sa_restart() {
+        let tf = trap_frame(0x2000);
+        let restart = trap_frame(0x1ffe);
+
+        assert_eq!(resume_context(&tf, None, None).ip(), 0x2000);
+        assert_eq!(resume_context(&tf, Some(restart), None).ip(), 0x1ffe);
+        let plain = handler(SigActionFlags::empty());
+        assert_eq!(
+            resume_context(&tf, Some(restart), Some(&plain)).ip(),
+            0x2000
+        );
+        let restarting = handler(SigActionFlags::SA_RESTART);
+        assert_eq!(
+            resume_context(&tf, Some(restart), Some(&restarting)).ip(),
+            0x1ffe
+        );
+    }
+
+    #[test]
+    fn forged_frame_keeps_privileged_state() {
+        let tf_user = trap_frame(0x2000);
+        let mut tf = tf_user;
+        #[cfg_attr(not(target_arch = "x86_64"), allow(unused_mut))]
+        let mut forged = trap_frame(0x3000);
+        #[cfg(target_arch = "x86_64")]
+        {
+            forged.cs = 0x8;
+        }
+        restore_user_context(&mut tf, forged);
+        assert_eq!(tf.ip(), 0x3000);
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(tf.cs, tf_user.cs);
+    }
+
+    #[test]
+    fn signal_names() {
+        assert_eq!(Signal::SIGTERM.name(), "SIGTERM");
+        assert_eq!(Signal::from_name("SIGKILL"), Some(Signal::SIGKILL));
+        assert_eq!(Signal::from_name("SIGFOO"), None);
+
+        for signo in 1..SIGRTMIN {
+            let sig = Signal::from_u32(signo).unwrap();
+            assert_eq!(Signal::from_name(sig.name()), Some(sig));
+            assert_eq!(Signal::from_name(sig.name()).unwrap().name(), sig.name());
+        }
+    }
+
+    #[test]
+    fn default_dispositions() {
+        let default = |sig: Signal| sig.default_disposition();
+        assert!(matches!(
+            default(Signal::SIGKILL),
+            SigDisposition::Terminate
+        ));
+        assert!(matches!(default(Signal::SIGCHLD), SigDisposition::Ignore));
+        assert!(matches!(default(Signal::SIGURG), SigDisposition::Ignore));
+        assert!(matches!(default(Signal::SIGWINCH), SigDisposition::Ignore));
+        assert!(matches!(default(Signal::SIGSTOP), SigDisposition::Stop));
+        assert!(matches!(default(Signal::SIGSEGV), SigDisposition::CoreDump));
+
+        assert!(Signal::is_real_time(32));
+        assert!(Signal::is_real_time(64));
+        assert!(!Signal::is_real_time(31));
+    }
+}
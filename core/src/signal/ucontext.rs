@@ -0,0 +1,559 @@
+//! `ucontext_t` as handed to signal handlers, laid out as the Linux
+//! `struct ucontext` of each architecture, including the FP/SIMD registers.
+//!
+//! The FP/SIMD registers are read from and written to the CPU directly: traps
+//! leave them alone, so they still hold the values of the user thread, and
+//! task switches save and restore them.
+
+use core::arch::asm;
+
+use axhal::arch::TrapFrame;
+
+use super::SigMask;
+
+/// `ss_flags` of a `stack_t` which is not in use
+const SS_DISABLE: i32 = 2;
+
+/// `stack_t`. Alternate signal stacks are not supported, so it always
+/// describes a disabled one.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SignalStack {
+    sp: usize,
+    flags: i32,
+    size: usize,
+}
+
+/// The `struct ucontext` of the signal frame.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UContext {
+    flags: usize,
+    link: usize,
+    stack: SignalStack,
+    #[cfg(not(target_arch = "x86_64"))]
+    sigmask: u64,
+    #[cfg(not(target_arch = "x86_64"))]
+    _unused: [u8; 120],
+    mcontext: MContext,
+    #[cfg(target_arch = "x86_64")]
+    sigmask: u64,
+    /// The rest of the C library's 1024-bit `sigset_t`
+    #[cfg(target_arch = "x86_64")]
+    _sigmask_rest: [u64; 15],
+    /// The `fxsave` area `mcontext.fpstate` points to
+    #[cfg(target_arch = "x86_64")]
+    fpregs: [u64; 64],
+}
+
+impl UContext {
+    /// Capture the user context in `tf`, the FP/SIMD registers of the CPU and
+    /// the signal mask `blocked`.
+    pub fn new(tf: &TrapFrame, blocked: SigMask) -> Self {
+        Self {
+            flags: 0,
+            link: 0,
+            stack: SignalStack {
+                sp: 0,
+                flags: SS_DISABLE,
+                size: 0,
+            },
+            sigmask: blocked.to_sigset(),
+            #[cfg(not(target_arch = "x86_64"))]
+            _unused: [0; 120],
+            #[cfg(target_arch = "x86_64")]
+            _sigmask_rest: [0; 15],
+            #[cfg(target_arch = "x86_64")]
+            fpregs: save_fxsave_area(),
+            mcontext: MContext::new(tf, blocked),
+        }
+    }
+
+    /// Tell where the context lives in user memory, so that the pointers in
+    /// it can be set.
+    #[cfg_attr(not(target_arch = "x86_64"), allow(unused_variables))]
+    pub fn set_user_addr(&mut self, addr: usize) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            self.mcontext.fpstate = addr + core::mem::offset_of!(UContext, fpregs);
+        }
+    }
+
+    /// The signal mask to restore.
+    pub fn sigmask(&self) -> SigMask {
+        SigMask::from_sigset(self.sigmask)
+    }
+
+    /// Write the registers saved in the context into `tf` and into the
+    /// FP/SIMD registers of the CPU.
+    ///
+    /// All of the context is under user control, so `tf` must be sanitized
+    /// afterwards.
+    pub fn restore(&self, tf: &mut TrapFrame) {
+        #[cfg(target_arch = "x86_64")]
+        restore_fxsave_area(&self.fpregs);
+        self.mcontext.restore(tf);
+    }
+}
+
+/// `struct sigcontext`
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct MContext {
+    r8: u64,
+    r9: u64,
+    r10: u64,
+    r11: u64,
+    r12: u64,
+    r13: u64,
+    r14: u64,
+    r15: u64,
+    rdi: u64,
+    rsi: u64,
+    rbp: u64,
+    rbx: u64,
+    rdx: u64,
+    rax: u64,
+    rcx: u64,
+    rsp: u64,
+    rip: u64,
+    eflags: u64,
+    cs: u16,
+    gs: u16,
+    fs: u16,
+    ss: u16,
+    err: u64,
+    trapno: u64,
+    oldmask: u64,
+    cr2: u64,
+    fpstate: usize,
+    _reserved: [u64; 8],
+}
+
+#[cfg(target_arch = "x86_64")]
+impl MContext {
+    fn new(tf: &TrapFrame, blocked: SigMask) -> Self {
+        Self {
+            r8: tf.r8,
+            r9: tf.r9,
+            r10: tf.r10,
+            r11: tf.r11,
+            r12: tf.r12,
+            r13: tf.r13,
+            r14: tf.r14,
+            r15: tf.r15,
+            rdi: tf.rdi,
+            rsi: tf.rsi,
+            rbp: tf.rbp,
+            rbx: tf.rbx,
+            rdx: tf.rdx,
+            rax: tf.rax,
+            rcx: tf.rcx,
+            rsp: tf.rsp,
+            rip: tf.rip,
+            eflags: tf.rflags,
+            cs: tf.cs as _,
+            gs: 0,
+            fs: 0,
+            ss: tf.ss as _,
+            err: tf.error_code,
+            trapno: tf.vector,
+            oldmask: blocked.to_sigset(),
+            cr2: 0,
+            fpstate: 0,
+            _reserved: [0; 8],
+        }
+    }
+
+    fn restore(&self, tf: &mut TrapFrame) {
+        tf.r8 = self.r8;
+        tf.r9 = self.r9;
+        tf.r10 = self.r10;
+        tf.r11 = self.r11;
+        tf.r12 = self.r12;
+        tf.r13 = self.r13;
+        tf.r14 = self.r14;
+        tf.r15 = self.r15;
+        tf.rdi = self.rdi;
+        tf.rsi = self.rsi;
+        tf.rbp = self.rbp;
+        tf.rbx = self.rbx;
+        tf.rdx = self.rdx;
+        tf.rax = self.rax;
+        tf.rcx = self.rcx;
+        tf.rsp = self.rsp;
+        tf.rip = self.rip;
+        tf.rflags = self.eflags;
+    }
+}
+
+/// The area `fxsave` stores the x87, MMX and SSE state to.
+#[cfg(target_arch = "x86_64")]
+#[repr(C, align(16))]
+struct FxsaveArea([u64; 64]);
+
+#[cfg(target_arch = "x86_64")]
+fn save_fxsave_area() -> [u64; 64] {
+    let mut area = FxsaveArea([0; 64]);
+    // SAFETY: `area` is 512 bytes and 16-byte aligned.
+    unsafe { asm!("fxsave64 [{}]", in(reg) area.0.as_mut_ptr(), options(nostack)) };
+    area.0
+}
+
+#[cfg(target_arch = "x86_64")]
+fn restore_fxsave_area(regs: &[u64; 64]) {
+    // `fxrstor` faults on the reserved upper half of MXCSR, which is the
+    // low half of the word at byte 24.
+    const MXCSR_RESERVED: u64 = 0xffff_0000;
+    let mut area = FxsaveArea(*regs);
+    area.0[3] &= !MXCSR_RESERVED;
+    // SAFETY: `area` is 512 bytes and 16-byte aligned.
+    unsafe { asm!("fxrstor64 [{}]", in(reg) area.0.as_ptr(), options(nostack)) };
+}
+
+/// `struct sigcontext`, whose `regs` are `x1` to `x31` like
+/// [`GeneralRegisters`](axhal::arch::GeneralRegisters).
+#[cfg(target_arch = "riscv64")]
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct MContext {
+    pc: usize,
+    regs: axhal::arch::GeneralRegisters,
+    fp: FpState,
+}
+
+/// The `d` variant of `union __riscv_fp_state`, padded to the size of the
+/// union.
+#[cfg(target_arch = "riscv64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FpState {
+    f: [u64; 32],
+    fcsr: u32,
+    _reserved: [u32; 67],
+}
+
+#[cfg(target_arch = "riscv64")]
+impl MContext {
+    fn new(tf: &TrapFrame, _blocked: SigMask) -> Self {
+        Self {
+            pc: tf.sepc,
+            regs: tf.regs,
+            fp: FpState::save(),
+        }
+    }
+
+    fn restore(&self, tf: &mut TrapFrame) {
+        tf.sepc = self.pc;
+        tf.regs = self.regs;
+        self.fp.restore();
+    }
+}
+
+#[cfg(target_arch = "riscv64")]
+impl FpState {
+    /// Whether the FPU is on, otherwise user space can not have used it.
+    fn enabled() -> bool {
+        const SSTATUS_FS: usize = 0x6000;
+        let sstatus: usize;
+        // SAFETY: Reading `sstatus` has no side effects.
+        unsafe { asm!("csrr {}, sstatus", out(reg) sstatus) };
+        sstatus & SSTATUS_FS != 0
+    }
+
+    fn save() -> Self {
+        let mut state = Self {
+            f: [0; 32],
+            fcsr: 0,
+            _reserved: [0; 67],
+        };
+        if Self::enabled() {
+            let fcsr: usize;
+            // SAFETY: `state.f` has room for the 32 registers.
+            unsafe {
+                asm!(
+                    r".irp i, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31",
+                    r"fsd f\i, \i * 8({f})",
+                    r".endr",
+                    "frcsr {fcsr}",
+                    f = in(reg) state.f.as_mut_ptr(),
+                    fcsr = out(reg) fcsr,
+                    options(nostack),
+                )
+            };
+            state.fcsr = fcsr as _;
+        }
+        state
+    }
+
+    fn restore(&self) {
+        if !Self::enabled() {
+            return;
+        }
+        // SAFETY: `self.f` holds the 32 registers.
+        unsafe {
+            asm!(
+                r".irp i, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31",
+                r"fld f\i, \i * 8({f})",
+                r".endr",
+                "fscsr {fcsr}",
+                f = in(reg) self.f.as_ptr(),
+                fcsr = in(reg) self.fcsr as usize & 0xff,
+                options(nostack),
+            )
+        };
+    }
+}
+
+/// `struct sigcontext`, whose `__reserved` space starts with the FP/SIMD
+/// record and is terminated by an empty record.
+#[cfg(target_arch = "aarch64")]
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct MContext {
+    fault_address: u64,
+    regs: [u64; 31],
+    sp: u64,
+    pc: u64,
+    pstate: u64,
+    fpsimd: FpsimdContext,
+    _reserved: [u8; 4096 - size_of::<FpsimdContext>()],
+}
+
+/// `magic` of a `struct fpsimd_context`
+#[cfg(target_arch = "aarch64")]
+const FPSIMD_MAGIC: u32 = 0x4650_8001;
+
+/// `struct fpsimd_context`
+#[cfg(target_arch = "aarch64")]
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct FpsimdContext {
+    magic: u32,
+    size: u32,
+    fpsr: u32,
+    fpcr: u32,
+    vregs: [u128; 32],
+}
+
+#[cfg(target_arch = "aarch64")]
+impl MContext {
+    fn new(tf: &TrapFrame, _blocked: SigMask) -> Self {
+        Self {
+            fault_address: 0,
+            regs: tf.r,
+            sp: tf.usp,
+            pc: tf.elr,
+            pstate: tf.spsr,
+            fpsimd: FpsimdContext::save(),
+            _reserved: [0; 4096 - size_of::<FpsimdContext>()],
+        }
+    }
+
+    fn restore(&self, tf: &mut TrapFrame) {
+        tf.r = self.regs;
+        tf.usp = self.sp;
+        tf.elr = self.pc;
+        tf.spsr = self.pstate;
+        if self.fpsimd.magic == FPSIMD_MAGIC {
+            self.fpsimd.restore();
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl FpsimdContext {
+    /// Whether FP/SIMD instructions do not trap, otherwise user space can
+    /// not have used them.
+    fn enabled() -> bool {
+        const CPACR_EL1_FPEN: u64 = 0b11 << 20;
+        let cpacr: u64;
+        // SAFETY: Reading `CPACR_EL1` has no side effects.
+        unsafe { asm!("mrs {}, cpacr_el1", out(reg) cpacr) };
+        cpacr & CPACR_EL1_FPEN == CPACR_EL1_FPEN
+    }
+
+    fn save() -> Self {
+        let mut context = Self {
+            magic: FPSIMD_MAGIC,
+            size: size_of::<Self>() as _,
+            fpsr: 0,
+            fpcr: 0,
+            vregs: [0; 32],
+        };
+        if Self::enabled() {
+            let (fpsr, fpcr): (u64, u64);
+            // SAFETY: `context.vregs` has room for the 32 registers.
+            unsafe {
+                asm!(
+                    ".arch_extension fp",
+                    ".arch_extension simd",
+                    r".irp i, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31",
+                    r"str q\i, [{v}, #\i * 16]",
+                    r".endr",
+                    "mrs {fpsr}, fpsr",
+                    "mrs {fpcr}, fpcr",
+                    v = in(reg) context.vregs.as_mut_ptr(),
+                    fpsr = out(reg) fpsr,
+                    fpcr = out(reg) fpcr,
+                    options(nostack),
+                )
+            };
+            context.fpsr = fpsr as _;
+            context.fpcr = fpcr as _;
+        }
+        context
+    }
+
+    fn restore(&self) {
+        if !Self::enabled() {
+            return;
+        }
+        // SAFETY: `self.vregs` holds the 32 registers.
+        unsafe {
+            asm!(
+                ".arch_extension fp",
+                ".arch_extension simd",
+                r".irp i, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31",
+                r"ldr q\i, [{v}, #\i * 16]",
+                r".endr",
+                "msr fpsr, {fpsr}",
+                "msr fpcr, {fpcr}",
+                v = in(reg) self.vregs.as_ptr(),
+                fpsr = in(reg) self.fpsr as u64,
+                fpcr = in(reg) self.fpcr as u64,
+                options(nostack),
+            )
+        };
+    }
+}
+
+/// `struct sigcontext`, whose `sc_extcontext` holds the FPU record and is
+/// terminated by an empty record.
+#[cfg(target_arch = "loongarch64")]
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct MContext {
+    pc: usize,
+    /// `r0` to `r31`, in the order of the fields of the trap frame's
+    /// registers
+    regs: [usize; 32],
+    flags: u32,
+    fpu: FpuContext,
+    end: [u64; 2],
+}
+
+/// `magic` of the `struct sctx_info` heading an FPU record
+#[cfg(target_arch = "loongarch64")]
+const FPU_CTX_MAGIC: u32 = 0x4650_5501;
+
+/// `struct sctx_info` followed by `struct fpu_context`
+#[cfg(target_arch = "loongarch64")]
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct FpuContext {
+    magic: u32,
+    size: u32,
+    _padding: u64,
+    regs: [u64; 32],
+    /// `fcc0` to `fcc7`, one per byte
+    fcc: [u8; 8],
+    fcsr: u32,
+}
+
+#[cfg(target_arch = "loongarch64")]
+impl MContext {
+    fn new(tf: &TrapFrame, _blocked: SigMask) -> Self {
+        Self {
+            pc: tf.era,
+            // SAFETY: The trap frame holds the 32 registers in order.
+            regs: unsafe { core::mem::transmute(tf.regs) },
+            flags: 0,
+            fpu: FpuContext::save(),
+            end: [0; 2],
+        }
+    }
+
+    fn restore(&self, tf: &mut TrapFrame) {
+        tf.era = self.pc;
+        let mut regs = self.regs;
+        regs[0] = 0;
+        // SAFETY: The trap frame holds the 32 registers in order.
+        tf.regs = unsafe { core::mem::transmute(regs) };
+        if self.fpu.magic == FPU_CTX_MAGIC {
+            self.fpu.restore();
+        }
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+impl FpuContext {
+    /// Whether the FPU is on, otherwise user space can not have used it.
+    fn enabled() -> bool {
+        const EUEN_FPE: usize = 1;
+        let euen: usize;
+        // SAFETY: Reading `EUEN` has no side effects.
+        unsafe { asm!("csrrd {}, 0x2", out(reg) euen) };
+        euen & EUEN_FPE != 0
+    }
+
+    fn save() -> Self {
+        let mut context = Self {
+            magic: FPU_CTX_MAGIC,
+            size: size_of::<Self>() as _,
+            _padding: 0,
+            regs: [0; 32],
+            fcc: [0; 8],
+            fcsr: 0,
+        };
+        if Self::enabled() {
+            let fcsr: usize;
+            // SAFETY: `context.regs` and `context.fcc` have room for the
+            // registers.
+            unsafe {
+                asm!(
+                    r".irp i, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31",
+                    r"fst.d $f\i, {f}, \i * 8",
+                    r".endr",
+                    r".irp i, 0,1,2,3,4,5,6,7",
+                    r"movcf2gr {tmp}, $fcc\i",
+                    r"st.b {tmp}, {fcc}, \i",
+                    r".endr",
+                    "movfcsr2gr {fcsr}, $fcsr0",
+                    f = in(reg) context.regs.as_mut_ptr(),
+                    fcc = in(reg) context.fcc.as_mut_ptr(),
+                    tmp = out(reg) _,
+                    fcsr = out(reg) fcsr,
+                    options(nostack),
+                )
+            };
+            context.fcsr = fcsr as _;
+        }
+        context
+    }
+
+    fn restore(&self) {
+        if !Self::enabled() {
+            return;
+        }
+        // SAFETY: `self.regs` and `self.fcc` hold the registers.
+        unsafe {
+            asm!(
+                r".irp i, 0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31",
+                r"fld.d $f\i, {f}, \i * 8",
+                r".endr",
+                r".irp i, 0,1,2,3,4,5,6,7",
+                r"ld.bu {tmp}, {fcc}, \i",
+                r"movgr2cf $fcc\i, {tmp}",
+                r".endr",
+                "movgr2fcsr $fcsr0, {fcsr}",
+                f = in(reg) self.regs.as_ptr(),
+                fcc = in(reg) self.fcc.as_ptr(),
+                tmp = out(reg) _,
+                fcsr = in(reg) self.fcsr as usize,
+                options(nostack),
+            )
+        };
+    }
+}
@@ -3,23 +3,24 @@
 use core::{
     alloc::Layout,
     cell::RefCell,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
 use alloc::{
-    collections::VecDeque,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     string::String,
     sync::{Arc, Weak},
 };
 use axhal::{
-    arch::UspaceContext,
-    time::{NANOS_PER_MICROS, NANOS_PER_SEC, monotonic_time_nanos},
+    arch::{TrapFrame, UspaceContext},
+    paging::MappingFlags,
+    time::{NANOS_PER_MICROS, NANOS_PER_SEC, TimeValue, monotonic_time_nanos, wall_time},
 };
 use axmm::{AddrSpace, kernel_aspace};
 use axns::{AxNamespace, AxNamespaceIf};
 use axprocess::{Pid, Process, ProcessGroup, Session, Thread};
 use axsync::Mutex;
-use axtask::{TaskExtRef, TaskInner, current};
+use axtask::{TaskExtRef, TaskInner, WaitQueue, current};
 use memory_addr::VirtAddrRange;
 use spin::{Once, RwLock};
 use weak_map::WeakMap;
@@ -27,7 +28,9 @@ use weak_map::WeakMap;
 use axsync::spin::SpinNoIrq;
 
 #[macro_use]
-use super::signal::{Signal, SigMask, SignalAction};
+use super::signal::{_NSIG, SigInfo, SigMask, Signal, SignalAction, access_user, send_signal_proc};
+use crate::futex::futex_wake;
+use crate::mm::access_user_memory;
 use crate::time::TimeStat;
 
 /// Create a new user task.
@@ -126,6 +129,134 @@ pub fn time_stat_output() -> (usize, usize, usize, usize) {
     )
 }
 
+/// An armed `ITIMER_REAL` timer, raising `SIGALRM` at `deadline` and then
+/// every `interval` if it is non-zero.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RealTimer {
+    /// Wall time at which the timer fires next
+    pub deadline: TimeValue,
+    /// Period to re-arm the timer with after it fired
+    pub interval: TimeValue,
+}
+
+/// Arm the `ITIMER_REAL` timer of the current process to fire after `value`,
+/// or disarm it if `value` is zero.
+///
+/// Returns the time left on the previous timer and its interval.
+pub fn set_real_timer(value: TimeValue, interval: TimeValue) -> (TimeValue, TimeValue) {
+    let curr = current();
+    let now = wall_time();
+    let timer = (!value.is_zero()).then(|| RealTimer {
+        deadline: now + value,
+        interval,
+    });
+    let old = core::mem::replace(
+        &mut *curr.task_ext().process_data().real_timer.lock(),
+        timer,
+    );
+    let process = curr.task_ext().thread.process();
+    {
+        let mut timers = REAL_TIMERS.lock();
+        if timer.is_some() {
+            timers.insert(process.pid(), Arc::downgrade(process));
+        } else {
+            timers.remove(&process.pid());
+        }
+    }
+    if timer.is_some() {
+        REAL_TIMER_TASK.call_once(|| {
+            axtask::spawn(run_real_timers);
+        });
+    }
+    REAL_TIMERS_CHANGED.store(true, Ordering::Release);
+    REAL_TIMER_WQ.notify_one(false);
+    old.map_or((TimeValue::ZERO, TimeValue::ZERO), |old| {
+        (old.deadline.saturating_sub(now), old.interval)
+    })
+}
+
+/// Processes with an armed `ITIMER_REAL` timer. The deadline itself lives in
+/// [`ProcessData::real_timer`].
+static REAL_TIMERS: SpinNoIrq<BTreeMap<Pid, Weak<Process>>> = SpinNoIrq::new(BTreeMap::new());
+/// Set when a timer is armed or disarmed so that the timer task recomputes
+/// its next deadline.
+static REAL_TIMERS_CHANGED: AtomicBool = AtomicBool::new(false);
+static REAL_TIMER_WQ: WaitQueue = WaitQueue::new();
+static REAL_TIMER_TASK: Once<()> = Once::new();
+
+/// The single task serving all `ITIMER_REAL` timers: it sleeps on the
+/// scheduler's timer list until the earliest deadline, raises `SIGALRM` for
+/// every expired timer and re-arms the periodic ones.
+fn run_real_timers() {
+    loop {
+        let now = wall_time();
+        let mut expired = alloc::vec::Vec::new();
+        let mut next: Option<TimeValue> = None;
+        REAL_TIMERS.lock().retain(|pid, process| {
+            let Some(process) = process.upgrade().filter(|p| !p.is_zombie()) else {
+                return false;
+            };
+            let proc_data: &ProcessData = process.data().unwrap();
+            let mut armed = proc_data.real_timer.lock();
+            let Some(timer) = armed.as_mut() else {
+                return false;
+            };
+            if timer.deadline <= now {
+                expired.push(*pid);
+                if timer.interval.is_zero() {
+                    *armed = None;
+                    return false;
+                }
+                timer.deadline += timer.interval;
+            }
+            next = Some(next.map_or(timer.deadline, |next| next.min(timer.deadline)));
+            true
+        });
+        for pid in expired {
+            let _ = send_signal_proc(pid as _, Signal::SIGALRM as _);
+        }
+
+        let changed = || REAL_TIMERS_CHANGED.swap(false, Ordering::Acquire);
+        match next {
+            Some(next) => {
+                REAL_TIMER_WQ.wait_timeout_until(next.saturating_sub(wall_time()), changed);
+            }
+            None => REAL_TIMER_WQ.wait_until(changed),
+        }
+    }
+}
+
+/// Clear the word at the current thread's `clear_child_tid` address as it
+/// exits and wake a thread waiting on it, e.g., in `pthread_join`. Nothing
+/// is written if the address is unset or not writable.
+pub fn clear_child_tid() {
+    let addr = current().task_ext().thread_data().clear_child_tid();
+    if addr == 0 || addr % align_of::<Pid>() != 0 {
+        return;
+    }
+    if access_user(addr, size_of::<Pid>(), MappingFlags::WRITE).is_ok() {
+        // SAFETY: The region has been checked and populated.
+        access_user_memory(|| unsafe { (addr as *mut Pid).write(0) });
+        futex_wake(addr, 1);
+    }
+}
+
+/// Tell the parent of the exited `process` about it: wake up the threads
+/// waiting for a child in `wait4`/`waitid` and send it `SIGCHLD`.
+///
+/// The waiters sleep on their `signal_wq`, so that signals interrupt them.
+pub fn notify_parent_exit(process: &Process) {
+    let Some(parent) = process.parent() else {
+        return;
+    };
+    for thread in parent.threads() {
+        if let Some(thread_data) = thread.data::<ThreadData>() {
+            thread_data.signal_wq.notify_all(false);
+        }
+    }
+    let _ = send_signal_proc(parent.pid() as _, Signal::SIGCHLD as _);
+}
+
 /// Extended data for [`Thread`].
 pub struct ThreadData {
     /// The clear thread tid field
@@ -135,12 +266,18 @@ pub struct ThreadData {
     /// When the thread exits, the kernel clears the word at this address if it is not NULL.
     pub clear_child_tid: AtomicUsize,
 
-    /// Pending signals
-    pub pending: SpinNoIrq<VecDeque<Signal>>,
+    /// The pending signals
+    pub pending: SpinNoIrq<VecDeque<SigInfo>>,
     /// Blocked signals
-    pub blocked: SigMask,
-    /// Saved signals
-    pub saved: SigMask,
+    pub blocked: SpinNoIrq<SigMask>,
+    /// Blocked signals to restore after the next signal is delivered, set by
+    /// `rt_sigsuspend`
+    pub saved: SpinNoIrq<Option<SigMask>>,
+    /// Threads waiting for a signal to arrive
+    pub signal_wq: WaitQueue,
+    /// Context to re-enter the last syscall with, if it was interrupted by a
+    /// signal
+    pub interrupted: SpinNoIrq<Option<TrapFrame>>,
 }
 
 impl ThreadData {
@@ -150,8 +287,10 @@ impl ThreadData {
         Self {
             clear_child_tid: AtomicUsize::new(0),
             pending: SpinNoIrq::new(VecDeque::new()),
-            blocked: SigMask::empty(),
-            saved: SigMask::empty(),
+            blocked: SpinNoIrq::new(SigMask::empty()),
+            saved: SpinNoIrq::new(None),
+            signal_wq: WaitQueue::new(),
+            interrupted: SpinNoIrq::new(None),
         }
     }
 
@@ -173,10 +312,15 @@ pub struct ProcessData {
     pub exe_path: RwLock<String>,
     /// The virtual memory address space.
     pub aspace: Arc<Mutex<AddrSpace>>,
-    /// Shared pending signals
-    pub shared: SpinNoIrq<VecDeque<Signal>>,
+    /// The shared pending signals
+    pub shared: SpinNoIrq<VecDeque<SigInfo>>,
+    /// The `ITIMER_REAL` timer, if armed
+    pub real_timer: SpinNoIrq<Option<RealTimer>>,
+    /// Signal actions, indexed by signal number
+    pub actions: Mutex<[SignalAction; _NSIG as usize + 1]>,
+    /// File descriptors to close on `execve`
+    pub cloexec_fds: Mutex<BTreeSet<i32>>,
     /// The resource namespace
-    pub actions: Mutex<[SignalAction; 32]>,
     pub ns: AxNamespace,
     /// The user heap bottom
     heap_bottom: AtomicUsize,
@@ -192,7 +336,9 @@ impl ProcessData {
             aspace,
             ns: AxNamespace::new_thread_local(),
             shared: SpinNoIrq::new(VecDeque::new()),
-            actions: Mutex::default(),
+            real_timer: SpinNoIrq::new(None),
+            actions: Mutex::new([SignalAction::default(); _NSIG as usize + 1]),
+            cloexec_fds: Mutex::new(BTreeSet::new()),
             heap_bottom: AtomicUsize::new(axconfig::plat::USER_HEAP_BASE),
             heap_top: AtomicUsize::new(axconfig::plat::USER_HEAP_BASE),
         }
@@ -1,6 +1,9 @@
 use alloc::{string::String, sync::Arc};
 use arceos_posix_api::FD_TABLE;
-use axfs::{CURRENT_DIR, CURRENT_DIR_PATH, api::set_current_dir};
+use axfs::{
+    CURRENT_DIR, CURRENT_DIR_PATH,
+    api::{canonicalize, set_current_dir},
+};
 use axhal::arch::UspaceContext;
 use axprocess::{Pid, init_proc};
 use axsync::Mutex;
@@ -18,6 +21,9 @@ pub fn run_user_app(args: &[String], envs: &[String]) -> Option<i32> {
         .expect("Failed to create user address space");
 
     let exe_path = args[0].clone();
+    // A relative `exe_path` is relative to the current directory, so resolve
+    // it before changing into its own.
+    let abs_exe_path = canonicalize(&exe_path).unwrap_or_else(|_| exe_path.clone());
     let (dir, name) = exe_path.rsplit_once('/').unwrap_or(("", &exe_path));
     set_current_dir(dir).expect("Failed to set current dir");
 
@@ -29,7 +35,7 @@ pub fn run_user_app(args: &[String], envs: &[String]) -> Option<i32> {
     let mut task = new_user_task(name, uctx, None);
     task.ctx_mut().set_page_table_root(uspace.page_table_root());
 
-    let process_data = ProcessData::new(exe_path, Arc::new(Mutex::new(uspace)));
+    let process_data = ProcessData::new(abs_exe_path, Arc::new(Mutex::new(uspace)));
 
     FD_TABLE
         .deref_from(&process_data.ns)